@@ -0,0 +1,3 @@
+CREATE INDEX idx_threads_thread_id ON threads (thread_id);
+
+CREATE INDEX idx_threads_user_id_desc ON threads (user_id, id DESC);
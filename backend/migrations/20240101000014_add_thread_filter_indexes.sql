@@ -0,0 +1,3 @@
+CREATE INDEX idx_threads_updated_at_desc ON threads (updated_at DESC);
+
+CREATE INDEX idx_threads_open_updated ON threads (is_open, updated_at DESC);
@@ -0,0 +1 @@
+CREATE INDEX idx_messages_created_at_id ON messages (created_at, id);
@@ -0,0 +1,16 @@
+CREATE TABLE thread_events (
+    id SERIAL PRIMARY KEY,
+    thread_id INTEGER NOT NULL REFERENCES threads(id),
+    event_type VARCHAR(32) NOT NULL,
+    actor_id VARCHAR(255),
+    actor_tag VARCHAR(255),
+    reason TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_thread_events_thread_created ON thread_events (thread_id, created_at);
+
+-- Backfill an open event for every existing thread so histories start at creation
+INSERT INTO thread_events (thread_id, event_type, actor_id, created_at)
+SELECT id, 'open', user_id, COALESCE(created_at, NOW())
+FROM threads;
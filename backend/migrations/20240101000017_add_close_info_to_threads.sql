@@ -0,0 +1,30 @@
+ALTER TABLE threads
+ADD COLUMN closed_at TIMESTAMPTZ,
+ADD COLUMN closed_by_id VARCHAR(255),
+ADD COLUMN closed_by_tag VARCHAR(255),
+ADD COLUMN close_reason TEXT;
+
+-- Backfilling must not bump updated_at, which the list filters and sorting rely on
+ALTER TABLE threads DISABLE TRIGGER update_threads_updated_at;
+
+-- Backfill closed threads from their most recent close event
+UPDATE threads t
+SET closed_at = e.created_at,
+    closed_by_id = e.actor_id,
+    closed_by_tag = e.actor_tag,
+    close_reason = e.reason
+FROM (
+    SELECT DISTINCT ON (thread_id) thread_id, created_at, actor_id, actor_tag, reason
+    FROM thread_events
+    WHERE event_type = 'close'
+    ORDER BY thread_id, created_at DESC, id DESC
+) e
+WHERE t.id = e.thread_id AND t.is_open = FALSE;
+
+-- Threads closed before events were recorded only have updated_at to go on
+UPDATE threads SET closed_at = updated_at WHERE is_open = FALSE AND closed_at IS NULL;
+
+ALTER TABLE threads ENABLE TRIGGER update_threads_updated_at;
+
+CREATE INDEX idx_threads_closed_by_closed_at ON threads (closed_by_tag, closed_at DESC)
+WHERE closed_by_tag IS NOT NULL;
@@ -0,0 +1,25 @@
+ALTER TABLE threads
+ADD COLUMN assigned_to_id VARCHAR(255),
+ADD COLUMN assigned_to_tag VARCHAR(255),
+ADD COLUMN assigned_at TIMESTAMPTZ;
+
+CREATE INDEX idx_threads_assigned_to_id ON threads (assigned_to_id, id DESC)
+WHERE assigned_to_id IS NOT NULL;
+
+CREATE TABLE thread_assignments (
+    id SERIAL PRIMARY KEY,
+    thread_id INTEGER NOT NULL REFERENCES threads(id),
+    assignee_id VARCHAR(255) NOT NULL,
+    assignee_tag VARCHAR(255) NOT NULL,
+    assigned_by_id VARCHAR(255),
+    assigned_by_tag VARCHAR(255),
+    assigned_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    unassigned_by_id VARCHAR(255),
+    unassigned_by_tag VARCHAR(255),
+    unassigned_at TIMESTAMPTZ,
+    CONSTRAINT chk_assignee_id_format CHECK (assignee_id ~ '^[0-9]+$')
+);
+
+CREATE INDEX idx_thread_assignments_thread_id ON thread_assignments (thread_id, assigned_at);
+
+CREATE INDEX idx_thread_assignments_assignee_id ON thread_assignments (assignee_id);
@@ -0,0 +1,16 @@
+CREATE TABLE tags (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(64) NOT NULL UNIQUE,
+    color VARCHAR(7),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT chk_tag_color_format CHECK (color IS NULL OR color ~ '^#[0-9A-Fa-f]{6}$')
+);
+
+CREATE TABLE thread_tags (
+    thread_id INTEGER NOT NULL REFERENCES threads(id),
+    tag_id INTEGER NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (thread_id, tag_id)
+);
+
+CREATE INDEX idx_thread_tags_tag_id ON thread_tags (tag_id, thread_id);
@@ -0,0 +1,15 @@
+ALTER TABLE threads ADD COLUMN status VARCHAR(32) NOT NULL DEFAULT 'open';
+
+-- Backfilling must not bump updated_at, which the list filters and sorting rely on
+ALTER TABLE threads DISABLE TRIGGER update_threads_updated_at;
+UPDATE threads SET status = 'closed' WHERE is_open = FALSE;
+ALTER TABLE threads ENABLE TRIGGER update_threads_updated_at;
+
+ALTER TABLE threads ADD CONSTRAINT chk_thread_status CHECK (
+    status IN ('open', 'waiting_on_user', 'waiting_on_staff', 'on_hold', 'resolved', 'closed')
+);
+
+CREATE INDEX idx_threads_status ON threads (status, id DESC);
+
+-- Structured details for events that carry more than actor and reason, e.g. status from/to
+ALTER TABLE thread_events ADD COLUMN metadata JSONB NOT NULL DEFAULT '{}'::jsonb;
@@ -0,0 +1,10 @@
+CREATE TABLE scheduled_closes (
+    thread_id INTEGER PRIMARY KEY REFERENCES threads(id),
+    close_at TIMESTAMPTZ NOT NULL,
+    scheduled_by_id VARCHAR(255),
+    scheduled_by_tag VARCHAR(255),
+    reason TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_scheduled_closes_close_at ON scheduled_closes (close_at);
@@ -0,0 +1,48 @@
+-- The API has always mapped uk_user_thread violations to 409, but no migration created it
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'uk_user_thread') THEN
+        -- Older databases may already hold the same user/channel pair more than once. Fold each
+        -- duplicate into one row, like a merge, so no messages or history are lost. The open
+        -- thread is kept if there is one, otherwise the oldest.
+        CREATE TEMP TABLE duplicate_threads ON COMMIT DROP AS
+        SELECT id, keep_id FROM (
+            SELECT id, FIRST_VALUE(id) OVER (
+                PARTITION BY user_id, thread_id ORDER BY is_open DESC, id ASC
+            ) AS keep_id
+            FROM threads
+        ) ranked
+        WHERE id <> keep_id;
+
+        INSERT INTO thread_messages (thread_id, message_id)
+        SELECT DISTINCT d.keep_id, tm.message_id
+        FROM thread_messages tm
+        JOIN duplicate_threads d ON d.id = tm.thread_id
+        ON CONFLICT DO NOTHING;
+        DELETE FROM thread_messages WHERE thread_id IN (SELECT id FROM duplicate_threads);
+
+        INSERT INTO thread_tags (thread_id, tag_id, created_at)
+        SELECT d.keep_id, tt.tag_id, MIN(tt.created_at)
+        FROM thread_tags tt
+        JOIN duplicate_threads d ON d.id = tt.thread_id
+        GROUP BY d.keep_id, tt.tag_id
+        ON CONFLICT DO NOTHING;
+        DELETE FROM thread_tags WHERE thread_id IN (SELECT id FROM duplicate_threads);
+
+        UPDATE notes n SET thread_id = d.keep_id
+        FROM duplicate_threads d WHERE n.thread_id = d.id;
+
+        UPDATE thread_events e SET thread_id = d.keep_id
+        FROM duplicate_threads d WHERE e.thread_id = d.id;
+
+        UPDATE thread_assignments a SET thread_id = d.keep_id
+        FROM duplicate_threads d WHERE a.thread_id = d.id;
+
+        -- The kept thread's own schedule (if any) wins
+        DELETE FROM scheduled_closes WHERE thread_id IN (SELECT id FROM duplicate_threads);
+
+        DELETE FROM threads WHERE id IN (SELECT id FROM duplicate_threads);
+
+        ALTER TABLE threads ADD CONSTRAINT uk_user_thread UNIQUE (user_id, thread_id);
+    END IF;
+END $$;
@@ -0,0 +1,19 @@
+ALTER TABLE messages
+ADD COLUMN discord_message_id VARCHAR(255),
+ADD COLUMN edited_at TIMESTAMP WITH TIME ZONE,
+ADD COLUMN deleted_at TIMESTAMP WITH TIME ZONE;
+
+CREATE UNIQUE INDEX idx_messages_discord_message_id ON messages (discord_message_id)
+WHERE discord_message_id IS NOT NULL;
+
+-- Content a message had before each edit, oldest first
+CREATE TABLE message_revisions (
+    id SERIAL PRIMARY KEY,
+    message_id UUID NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+    content TEXT NOT NULL,
+    attachments JSONB NOT NULL DEFAULT '[]',
+    edited_by_id VARCHAR(255),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_message_revisions_message_id ON message_revisions (message_id, created_at);
@@ -0,0 +1,20 @@
+ALTER TABLE messages ADD COLUMN kind VARCHAR(32);
+
+-- Before this column the only signal was whether the author is the thread's user
+UPDATE messages m
+SET kind = CASE
+    WHEN EXISTS (
+        SELECT 1 FROM thread_messages tm
+        JOIN threads t ON t.id = tm.thread_id
+        WHERE tm.message_id = m.id AND t.user_id = m.author_id
+    ) THEN 'user_inbound'
+    WHEN m.content LIKE '[MACRO:%' OR m.content LIKE '[QUICK REPLY:%' THEN 'macro'
+    ELSE 'staff_reply'
+END;
+
+ALTER TABLE messages ALTER COLUMN kind SET NOT NULL;
+
+ALTER TABLE messages ADD CONSTRAINT chk_message_kind
+CHECK (kind IN ('user_inbound', 'staff_reply', 'staff_anonymous_reply', 'system', 'macro'));
+
+CREATE INDEX idx_messages_kind_created_at ON messages (kind, created_at);
@@ -0,0 +1,31 @@
+CREATE TABLE attachments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    message_id UUID NOT NULL REFERENCES messages(id) ON DELETE CASCADE,
+    discord_id VARCHAR(255),
+    url TEXT NOT NULL,
+    filename VARCHAR(255) NOT NULL,
+    content_type VARCHAR(255),
+    size BIGINT NOT NULL DEFAULT 0,
+    position INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_attachments_message_id ON attachments (message_id, position);
+
+-- Copy what we can out of the old free-form JSONB; entries without a URL were never usable
+INSERT INTO attachments (message_id, discord_id, url, filename, content_type, size, position, created_at)
+SELECT
+    m.id,
+    a.value->>'discord_id',
+    a.value->>'url',
+    LEFT(COALESCE(NULLIF(a.value->>'filename', ''), 'attachment'), 255),
+    NULLIF(LEFT(a.value->>'content_type', 255), 'unknown'),
+    CASE WHEN a.value->>'size' ~ '^[0-9]+$' THEN (a.value->>'size')::BIGINT ELSE 0 END,
+    (a.ordinality - 1)::INTEGER,
+    m.created_at
+FROM messages m
+CROSS JOIN LATERAL jsonb_array_elements(
+    CASE WHEN jsonb_typeof(m.attachments) = 'array' THEN m.attachments ELSE '[]'::jsonb END
+) WITH ORDINALITY AS a(value, ordinality)
+WHERE jsonb_typeof(a.value) = 'object'
+AND COALESCE(a.value->>'url', '') != '';
@@ -0,0 +1,9 @@
+ALTER TABLE attachments
+ADD COLUMN storage_key TEXT,
+ADD COLUMN checksum_sha256 VARCHAR(64),
+ADD COLUMN archived_at TIMESTAMP WITH TIME ZONE,
+ADD COLUMN archive_attempts INTEGER NOT NULL DEFAULT 0,
+ADD COLUMN archive_error TEXT;
+
+CREATE INDEX idx_attachments_pending_archive ON attachments (created_at)
+WHERE storage_key IS NULL;
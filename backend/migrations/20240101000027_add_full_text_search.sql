@@ -0,0 +1,9 @@
+ALTER TABLE messages
+ADD COLUMN content_tsv TSVECTOR GENERATED ALWAYS AS (to_tsvector('english', COALESCE(content, ''))) STORED;
+
+ALTER TABLE notes
+ADD COLUMN content_tsv TSVECTOR GENERATED ALWAYS AS (to_tsvector('english', COALESCE(content, ''))) STORED;
+
+CREATE INDEX idx_messages_content_tsv ON messages USING GIN (content_tsv);
+
+CREATE INDEX idx_notes_content_tsv ON notes USING GIN (content_tsv);
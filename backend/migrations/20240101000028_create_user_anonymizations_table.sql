@@ -0,0 +1,12 @@
+-- Audit trail of right-to-erasure requests; the user ID is kept so we can prove it was honoured
+CREATE TABLE user_anonymizations (
+    id SERIAL PRIMARY KEY,
+    user_id VARCHAR(255) NOT NULL,
+    requested_by_id VARCHAR(255) NOT NULL,
+    requested_by_tag VARCHAR(255) NOT NULL,
+    reason TEXT,
+    counts JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_user_anonymizations_user ON user_anonymizations (user_id, created_at);
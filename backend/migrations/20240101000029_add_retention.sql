@@ -0,0 +1,11 @@
+-- Set when the retention job scrubs a closed thread; a later close makes it eligible again
+ALTER TABLE threads ADD COLUMN content_pruned_at TIMESTAMPTZ;
+
+CREATE INDEX idx_threads_closed_at ON threads (closed_at) WHERE is_open = FALSE;
+
+CREATE TABLE retention_runs (
+    id SERIAL PRIMARY KEY,
+    mode VARCHAR(16) NOT NULL,
+    counts JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
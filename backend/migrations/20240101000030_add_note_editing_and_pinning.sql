@@ -0,0 +1,19 @@
+ALTER TABLE notes
+ADD COLUMN pinned BOOLEAN NOT NULL DEFAULT FALSE,
+ADD COLUMN edited_at TIMESTAMP WITH TIME ZONE,
+ADD COLUMN deleted_at TIMESTAMP WITH TIME ZONE;
+
+-- Content a note had before each edit, oldest first
+CREATE TABLE note_revisions (
+    id SERIAL PRIMARY KEY,
+    note_id UUID NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+    content TEXT NOT NULL,
+    edited_by_id VARCHAR(255),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_note_revisions_note_id ON note_revisions (note_id, created_at);
+
+-- Pinned notes are listed first
+CREATE INDEX idx_notes_thread_pinned ON notes (thread_id, pinned DESC, created_at)
+WHERE deleted_at IS NULL;
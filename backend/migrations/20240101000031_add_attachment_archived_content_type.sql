@@ -0,0 +1,2 @@
+-- Content type reported by the CDN when the file was archived; the client-reported one isn't trusted for serving
+ALTER TABLE attachments ADD COLUMN archived_content_type VARCHAR(255);
@@ -0,0 +1,46 @@
+-- The API maps idx_threads_user_open violations to 409, but no migration created it
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_class WHERE relname = 'idx_threads_user_open') THEN
+        -- Races may already have left a user with more than one open thread. Fold the extra
+        -- ones into the oldest, as for uk_user_thread, so no messages or history are lost.
+        CREATE TEMP TABLE duplicate_open_threads ON COMMIT DROP AS
+        SELECT id, keep_id FROM (
+            SELECT id, MIN(id) OVER (PARTITION BY user_id) AS keep_id
+            FROM threads
+            WHERE is_open = TRUE
+        ) ranked
+        WHERE id <> keep_id;
+
+        INSERT INTO thread_messages (thread_id, message_id)
+        SELECT DISTINCT d.keep_id, tm.message_id
+        FROM thread_messages tm
+        JOIN duplicate_open_threads d ON d.id = tm.thread_id
+        ON CONFLICT DO NOTHING;
+        DELETE FROM thread_messages WHERE thread_id IN (SELECT id FROM duplicate_open_threads);
+
+        INSERT INTO thread_tags (thread_id, tag_id, created_at)
+        SELECT d.keep_id, tt.tag_id, MIN(tt.created_at)
+        FROM thread_tags tt
+        JOIN duplicate_open_threads d ON d.id = tt.thread_id
+        GROUP BY d.keep_id, tt.tag_id
+        ON CONFLICT DO NOTHING;
+        DELETE FROM thread_tags WHERE thread_id IN (SELECT id FROM duplicate_open_threads);
+
+        UPDATE notes n SET thread_id = d.keep_id
+        FROM duplicate_open_threads d WHERE n.thread_id = d.id;
+
+        UPDATE thread_events e SET thread_id = d.keep_id
+        FROM duplicate_open_threads d WHERE e.thread_id = d.id;
+
+        UPDATE thread_assignments a SET thread_id = d.keep_id
+        FROM duplicate_open_threads d WHERE a.thread_id = d.id;
+
+        -- The kept thread's own schedule (if any) wins
+        DELETE FROM scheduled_closes WHERE thread_id IN (SELECT id FROM duplicate_open_threads);
+
+        DELETE FROM threads WHERE id IN (SELECT id FROM duplicate_open_threads);
+
+        CREATE UNIQUE INDEX idx_threads_user_open ON threads (user_id) WHERE is_open = TRUE;
+    END IF;
+END $$;
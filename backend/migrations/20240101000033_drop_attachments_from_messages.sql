@@ -0,0 +1,2 @@
+-- The attachments table is the only store now; its migration already copied the usable entries
+ALTER TABLE messages DROP COLUMN attachments;
@@ -0,0 +1,3 @@
+-- The legacy JSONB copy never checked URL schemes, so a javascript: or data: URL could have been
+-- carried over into attachments. The API only ever accepted http(s), so these are dropped.
+DELETE FROM attachments WHERE url !~ '^https?://';
@@ -1,3 +1,4 @@
+use crate::auto_close::SYSTEM_ACTOR_ID;
 use actix_web::{get, post, web, HttpResponse, Responder};
 use serde::Serialize;
 use sqlx::{FromRow, PgPool};
@@ -41,7 +42,23 @@ struct ModeratorActivity {
     moderator_tag: String,
     message_count: i64,
     note_count: i64,
-    threads_closed: i32,
+    threads_closed: i64,
+}
+
+#[derive(Serialize, FromRow)]
+struct TagBreakdown {
+    tag_id: i32,
+    name: String,
+    color: Option<String>,
+    thread_count: i64,
+    open_threads: i64,
+    threads_this_month: i64,
+}
+
+#[derive(Serialize, FromRow)]
+struct StatusBreakdown {
+    status: String,
+    thread_count: i64,
 }
 
 #[derive(Serialize, FromRow)]
@@ -109,9 +126,7 @@ async fn get_analytics_overview(pool: web::Data<PgPool>) -> impl Responder {
             SELECT tm.thread_id, MIN(m.created_at) as created_at
             FROM thread_messages tm
             JOIN messages m ON tm.message_id = m.id
-            WHERE m.author_id != (
-                SELECT user_id FROM threads t WHERE t.id = tm.thread_id
-            )
+            WHERE m.kind IN ('staff_reply', 'staff_anonymous_reply', 'macro')
             GROUP BY tm.thread_id
         ) first_mod_message ON threads.id = first_mod_message.thread_id
         WHERE threads.created_at IS NOT NULL
@@ -239,14 +254,15 @@ async fn get_moderator_activity(pool: web::Data<PgPool>) -> impl Responder {
         sqlx::query_as::<_, ModeratorActivity>(
             r#"
         SELECT 
-            COALESCE(m.author_tag, n.author_tag, 'Unknown') as "moderator_tag",
+            COALESCE(m.author_tag, n.author_tag, c.closed_by_tag, 'Unknown') as "moderator_tag",
             COALESCE(message_count, 0) as "message_count",
             COALESCE(note_count, 0) as "note_count",
-            0 as "threads_closed"
+            COALESCE(closed_count, 0) as "threads_closed"
         FROM (
             SELECT author_tag, COUNT(*) as message_count
             FROM messages 
             WHERE created_at >= CURRENT_DATE - INTERVAL '30 days'
+            AND kind IN ('staff_reply', 'staff_anonymous_reply', 'macro')
             GROUP BY author_tag
         ) m
         FULL OUTER JOIN (
@@ -255,9 +271,18 @@ async fn get_moderator_activity(pool: web::Data<PgPool>) -> impl Responder {
             WHERE created_at >= CURRENT_DATE - INTERVAL '30 days'
             GROUP BY author_tag
         ) n ON m.author_tag = n.author_tag
-        ORDER BY (COALESCE(message_count, 0) + COALESCE(note_count, 0)) DESC
+        FULL OUTER JOIN (
+            SELECT closed_by_tag, COUNT(*) as closed_count
+            FROM threads
+            WHERE closed_by_tag IS NOT NULL
+            AND closed_by_id IS DISTINCT FROM $1
+            AND closed_at >= CURRENT_DATE - INTERVAL '30 days'
+            GROUP BY closed_by_tag
+        ) c ON COALESCE(m.author_tag, n.author_tag) = c.closed_by_tag
+        ORDER BY (COALESCE(message_count, 0) + COALESCE(note_count, 0) + COALESCE(closed_count, 0)) DESC
         "#,
         )
+        .bind(SYSTEM_ACTOR_ID)
         .fetch_all(pool.get_ref())
         .await;
 
@@ -285,9 +310,7 @@ async fn get_response_times(pool: web::Data<PgPool>) -> impl Responder {
                 SELECT tm.thread_id, MIN(m.created_at) as created_at
                 FROM thread_messages tm
                 JOIN messages m ON tm.message_id = m.id
-                WHERE m.author_id != (
-                    SELECT user_id FROM threads t WHERE t.id = tm.thread_id
-                )
+                WHERE m.kind IN ('staff_reply', 'staff_anonymous_reply', 'macro')
                 GROUP BY tm.thread_id
             ) first_mod_message ON threads.id = first_mod_message.thread_id
             WHERE threads.created_at >= CURRENT_DATE - INTERVAL '30 days'
@@ -297,11 +320,11 @@ async fn get_response_times(pool: web::Data<PgPool>) -> impl Responder {
         // Average resolution time - benefits from new created_at indexes
         sqlx::query_scalar::<_, Option<f64>>(
             r#"
-            SELECT AVG(EXTRACT(EPOCH FROM (updated_at - created_at)) / 3600.0)
+            SELECT AVG(EXTRACT(EPOCH FROM (COALESCE(closed_at, updated_at) - created_at)) / 3600.0)
             FROM threads
             WHERE is_open = false 
             AND created_at >= CURRENT_DATE - INTERVAL '30 days'
-            AND updated_at IS NOT NULL
+            AND COALESCE(closed_at, updated_at) IS NOT NULL
             "#,
         ).fetch_optional(pool.get_ref()),
 
@@ -314,9 +337,7 @@ async fn get_response_times(pool: web::Data<PgPool>) -> impl Responder {
                 SELECT tm.thread_id, MIN(m.created_at) as created_at
                 FROM thread_messages tm
                 JOIN messages m ON tm.message_id = m.id
-                WHERE m.author_id != (
-                    SELECT user_id FROM threads t WHERE t.id = tm.thread_id
-                )
+                WHERE m.kind IN ('staff_reply', 'staff_anonymous_reply', 'macro')
                 GROUP BY tm.thread_id
             ) first_mod_message ON threads.id = first_mod_message.thread_id
             WHERE threads.created_at >= CURRENT_DATE - INTERVAL '30 days'
@@ -337,6 +358,62 @@ async fn get_response_times(pool: web::Data<PgPool>) -> impl Responder {
     HttpResponse::Ok().json(metrics)
 }
 
+#[get("/analytics/tags")]
+async fn get_tag_breakdown(pool: web::Data<PgPool>) -> impl Responder {
+    let breakdown_result = sqlx::query_as::<_, TagBreakdown>(
+        r#"
+        SELECT
+            tg.id as "tag_id",
+            tg.name as "name",
+            tg.color as "color",
+            COUNT(t.id) as "thread_count",
+            COUNT(t.id) FILTER (WHERE t.is_open = true) as "open_threads",
+            COUNT(t.id) FILTER (WHERE t.created_at >= CURRENT_DATE - INTERVAL '30 days') as "threads_this_month"
+        FROM tags tg
+        LEFT JOIN thread_tags tt ON tt.tag_id = tg.id
+        LEFT JOIN threads t ON t.id = tt.thread_id
+        GROUP BY tg.id, tg.name, tg.color
+        ORDER BY COUNT(t.id) DESC, tg.name
+        "#,
+    )
+    .fetch_all(pool.get_ref())
+    .await;
+
+    match breakdown_result {
+        Ok(breakdown) => HttpResponse::Ok().json(breakdown),
+        Err(e) => {
+            eprintln!("Database error fetching tag breakdown: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch tag breakdown"
+            }))
+        }
+    }
+}
+
+#[get("/analytics/status")]
+async fn get_status_breakdown(pool: web::Data<PgPool>) -> impl Responder {
+    let breakdown_result = sqlx::query_as::<_, StatusBreakdown>(
+        r#"
+        SELECT status as "status", COUNT(*) as "thread_count"
+        FROM threads
+        GROUP BY status
+        ORDER BY COUNT(*) DESC
+        "#,
+    )
+    .fetch_all(pool.get_ref())
+    .await;
+
+    match breakdown_result {
+        Ok(breakdown) => HttpResponse::Ok().json(breakdown),
+        Err(e) => {
+            eprintln!("Database error fetching status breakdown: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch status breakdown"
+            }))
+        }
+    }
+}
+
 #[post("/analytics/refresh")]
 async fn refresh_analytics(pool: web::Data<PgPool>) -> impl Responder {
     // Refresh the materialized view for up-to-date analytics
@@ -0,0 +1,328 @@
+use crate::auth;
+use crate::db;
+use crate::storage::{self, AttachmentStore};
+use crate::structs::AnonymizeUser;
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool};
+
+const ANONYMIZED_TAG: &str = "Deleted User";
+const ANONYMIZED_CONTENT: &str = "[removed at the user's request]";
+
+#[derive(Deserialize)]
+struct AnonymizeQuery {
+    dry_run: Option<bool>,
+}
+
+#[derive(Serialize, Default)]
+struct AnonymizationReport {
+    messages: u64,
+    message_revisions: u64,
+    attachments: u64,
+    notes: u64,
+    note_revisions: u64,
+    blocked_users: u64,
+    threads: u64,
+    events: u64,
+    assignments: u64,
+    scheduled_closes: u64,
+}
+
+impl AnonymizationReport {
+    fn total(&self) -> u64 {
+        self.messages
+            + self.message_revisions
+            + self.attachments
+            + self.notes
+            + self.note_revisions
+            + self.blocked_users
+            + self.threads
+            + self.events
+            + self.assignments
+            + self.scheduled_closes
+    }
+}
+
+// Scrubs names and content in place. IDs, kinds and timestamps stay so thread history and
+// analytics still add up. Returns the storage keys of archived attachments to delete once the
+// transaction has committed.
+async fn anonymize_user_rows(
+    conn: &mut PgConnection,
+    user_id: &str,
+) -> Result<(AnonymizationReport, Vec<String>), sqlx::Error> {
+    let mut report = AnonymizationReport::default();
+
+    let storage_keys: Vec<Option<String>> = sqlx::query_scalar(
+        r#"
+        DELETE FROM attachments
+        WHERE message_id IN (SELECT id FROM messages WHERE author_id = $1)
+        RETURNING storage_key
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    report.attachments = storage_keys.len() as u64;
+
+    report.message_revisions = sqlx::query(
+        r#"
+        UPDATE message_revisions SET content = $2, attachments = '[]'::jsonb
+        WHERE message_id IN (SELECT id FROM messages WHERE author_id = $1)
+        "#,
+    )
+    .bind(user_id)
+    .bind(ANONYMIZED_CONTENT)
+    .execute(&mut *conn)
+    .await?
+    .rows_affected();
+
+    report.messages = sqlx::query(
+        r#"
+        UPDATE messages SET author_tag = $2, content = $3
+        WHERE author_id = $1
+        "#,
+    )
+    .bind(user_id)
+    .bind(ANONYMIZED_TAG)
+    .bind(ANONYMIZED_CONTENT)
+    .execute(&mut *conn)
+    .await?
+    .rows_affected();
+
+    report.notes =
+        sqlx::query("UPDATE notes SET author_tag = $2, content = $3 WHERE author_id = $1")
+            .bind(user_id)
+            .bind(ANONYMIZED_TAG)
+            .bind(ANONYMIZED_CONTENT)
+            .execute(&mut *conn)
+            .await?
+            .rows_affected();
+
+    report.note_revisions = sqlx::query(
+        r#"
+        UPDATE note_revisions SET content = $2
+        WHERE note_id IN (SELECT id FROM notes WHERE author_id = $1)
+        "#,
+    )
+    .bind(user_id)
+    .bind(ANONYMIZED_CONTENT)
+    .execute(&mut *conn)
+    .await?
+    .rows_affected();
+
+    // Block records keep the user ID so an anonymized user stays blocked
+    report.blocked_users = sqlx::query(
+        r#"
+        UPDATE blocked_users SET
+            user_tag = CASE WHEN user_id = $1 THEN $2 ELSE user_tag END,
+            blocked_by_tag = CASE WHEN blocked_by = $1 THEN $2 ELSE blocked_by_tag END
+        WHERE user_id = $1 OR blocked_by = $1
+        "#,
+    )
+    .bind(user_id)
+    .bind(ANONYMIZED_TAG)
+    .execute(&mut *conn)
+    .await?
+    .rows_affected();
+
+    report.threads = sqlx::query(
+        r#"
+        UPDATE threads SET
+            closed_by_tag = CASE WHEN closed_by_id = $1 THEN $2 ELSE closed_by_tag END,
+            assigned_to_tag = CASE WHEN assigned_to_id = $1 THEN $2 ELSE assigned_to_tag END
+        WHERE closed_by_id = $1 OR assigned_to_id = $1
+        "#,
+    )
+    .bind(user_id)
+    .bind(ANONYMIZED_TAG)
+    .execute(&mut *conn)
+    .await?
+    .rows_affected();
+
+    report.events = sqlx::query("UPDATE thread_events SET actor_tag = $2 WHERE actor_id = $1")
+        .bind(user_id)
+        .bind(ANONYMIZED_TAG)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+
+    report.assignments = sqlx::query(
+        r#"
+        UPDATE thread_assignments SET
+            assignee_tag = CASE WHEN assignee_id = $1 THEN $2 ELSE assignee_tag END,
+            assigned_by_tag = CASE WHEN assigned_by_id = $1 THEN $2 ELSE assigned_by_tag END,
+            unassigned_by_tag = CASE WHEN unassigned_by_id = $1 THEN $2 ELSE unassigned_by_tag END
+        WHERE assignee_id = $1 OR assigned_by_id = $1 OR unassigned_by_id = $1
+        "#,
+    )
+    .bind(user_id)
+    .bind(ANONYMIZED_TAG)
+    .execute(&mut *conn)
+    .await?
+    .rows_affected();
+
+    report.scheduled_closes =
+        sqlx::query("UPDATE scheduled_closes SET scheduled_by_tag = $2 WHERE scheduled_by_id = $1")
+            .bind(user_id)
+            .bind(ANONYMIZED_TAG)
+            .execute(&mut *conn)
+            .await?
+            .rows_affected();
+
+    Ok((report, storage_keys.into_iter().flatten().collect()))
+}
+
+#[post("/users/{user_id}/anonymize")]
+async fn anonymize_user(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    store: Option<web::Data<dyn AttachmentStore>>,
+    user_id: web::Path<String>,
+    query: web::Query<AnonymizeQuery>,
+    anonymize_data: web::Json<AnonymizeUser>,
+) -> Result<impl Responder> {
+    if !auth::is_admin(&req) {
+        return Ok(HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Admin access required"
+        })));
+    }
+
+    let user_id = user_id.into_inner();
+    let dry_run = query.dry_run.unwrap_or(false);
+
+    // Validate user ID format (Discord IDs are numeric)
+    if user_id.is_empty() || !user_id.chars().all(|c| c.is_ascii_digit()) {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Invalid user ID format"
+        })));
+    }
+
+    if anonymize_data.requested_by_id.is_empty()
+        || !anonymize_data
+            .requested_by_id
+            .chars()
+            .all(|c| c.is_ascii_digit())
+    {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Invalid requested_by_id format"
+        })));
+    }
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Database error starting transaction: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to anonymize user"
+            })));
+        }
+    };
+
+    let (report, storage_keys) = match anonymize_user_rows(&mut tx, &user_id).await {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("Database error anonymizing user {}: {}", user_id, e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to anonymize user"
+            })));
+        }
+    };
+
+    if report.total() == 0 {
+        return Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "No data found for user"
+        })));
+    }
+
+    let audit_result = sqlx::query_as::<_, db::UserAnonymization>(
+        r#"
+        INSERT INTO user_anonymizations (user_id, requested_by_id, requested_by_tag, reason, counts)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING *
+        "#,
+    )
+    .bind(&user_id)
+    .bind(&anonymize_data.requested_by_id)
+    .bind(&anonymize_data.requested_by_tag)
+    .bind(&anonymize_data.reason)
+    .bind(sqlx::types::Json(&report))
+    .fetch_one(&mut *tx)
+    .await;
+
+    let audit = match audit_result {
+        Ok(audit) => audit,
+        Err(e) => {
+            eprintln!("Database error recording anonymization: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to anonymize user"
+            })));
+        }
+    };
+
+    let finish_result = if dry_run {
+        tx.rollback().await
+    } else {
+        tx.commit().await
+    };
+
+    if let Err(e) = finish_result {
+        eprintln!("Database error finishing anonymization: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to anonymize user"
+        })));
+    }
+
+    if dry_run {
+        return Ok(HttpResponse::Ok().json(serde_json::json!({
+            "user_id": user_id,
+            "dry_run": true,
+            "anonymized": report
+        })));
+    }
+
+    if let Some(store) = &store {
+        storage::delete_keys(store.get_ref(), &storage_keys).await;
+    }
+
+    println!(
+        "User {} anonymized by {} ({}), audit record {}",
+        user_id, anonymize_data.requested_by_tag, anonymize_data.requested_by_id, audit.id
+    );
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "user_id": user_id,
+        "dry_run": false,
+        "anonymized": report,
+        "audit": audit
+    })))
+}
+
+#[get("/users/{user_id}/anonymizations")]
+async fn get_user_anonymizations(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    user_id: web::Path<String>,
+) -> impl Responder {
+    if !auth::is_admin(&req) {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Admin access required"
+        }));
+    }
+
+    let anonymizations_result = sqlx::query_as::<_, db::UserAnonymization>(
+        "SELECT * FROM user_anonymizations WHERE user_id = $1 ORDER BY created_at ASC, id ASC",
+    )
+    .bind(user_id.into_inner())
+    .fetch_all(pool.get_ref())
+    .await;
+
+    match anonymizations_result {
+        Ok(anonymizations) => HttpResponse::Ok().json(anonymizations),
+        Err(e) => {
+            eprintln!("Database error fetching anonymizations: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch anonymizations"
+            }))
+        }
+    }
+}
@@ -0,0 +1,267 @@
+use crate::db;
+use crate::storage::{self, AttachmentStore, LocalStore};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+
+const MAX_ARCHIVE_ATTEMPTS: i32 = 3;
+const ARCHIVE_BATCH_SIZE: i64 = 50;
+
+// Attachment URLs come from clients, so only Discord's CDN is ever fetched or redirected to
+const ALLOWED_HOSTS: [&str; 2] = ["cdn.discordapp.com", "media.discordapp.net"];
+
+pub fn is_discord_cdn(url: &reqwest::Url) -> bool {
+    url.scheme() == "https"
+        && url
+            .host_str()
+            .is_some_and(|host| ALLOWED_HOSTS.contains(&host))
+}
+
+pub struct ArchiveConfig {
+    pub storage_path: String,
+    pub max_bytes: u64,
+    pub allowed_types: Vec<String>,
+    pub interval_secs: u64,
+}
+
+impl ArchiveConfig {
+    // Archiving is off unless ATTACHMENT_STORAGE_PATH is set
+    pub fn from_env() -> Option<Self> {
+        let storage_path = env::var("ATTACHMENT_STORAGE_PATH")
+            .ok()
+            .filter(|path| !path.is_empty())?;
+
+        let max_bytes = env::var("ATTACHMENT_MAX_BYTES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(25 * 1024 * 1024); // Discord's default upload limit
+
+        let allowed_types = env::var("ATTACHMENT_ALLOWED_TYPES")
+            .unwrap_or_else(|_| "image/,video/,audio/,application/pdf,text/plain".to_string())
+            .split(',')
+            .map(|t| t.trim().to_ascii_lowercase())
+            .filter(|t| !t.is_empty())
+            .collect();
+
+        let interval_secs = env::var("ATTACHMENT_ARCHIVE_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|secs| *secs > 0)
+            .unwrap_or(60);
+
+        Some(Self {
+            storage_path,
+            max_bytes,
+            allowed_types,
+            interval_secs,
+        })
+    }
+
+    pub fn store(&self) -> Arc<dyn AttachmentStore> {
+        Arc::new(LocalStore::new(&self.storage_path))
+    }
+
+    // Redirects are off so an allowed host can't bounce the download somewhere internal
+    pub fn client(&self) -> reqwest::Result<reqwest::Client> {
+        reqwest::Client::builder()
+            .timeout(Duration::from_secs(60))
+            .connect_timeout(Duration::from_secs(10))
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+    }
+
+    // Entries ending in "/" match a whole family (image/), anything else must match exactly
+    fn is_allowed_type(&self, content_type: &str) -> bool {
+        let content_type = content_type
+            .split(';')
+            .next()
+            .unwrap_or("")
+            .trim()
+            .to_ascii_lowercase();
+
+        self.allowed_types.iter().any(|allowed| {
+            if allowed.ends_with('/') {
+                content_type.starts_with(allowed.as_str())
+            } else {
+                content_type == *allowed
+            }
+        })
+    }
+}
+
+enum ArchiveError {
+    // The attachment breaks the size/type policy; retrying won't help
+    Rejected(String),
+    // Network, storage or database trouble that may clear up on the next run
+    Failed(String),
+}
+
+async fn download(
+    client: &reqwest::Client,
+    attachment: &db::Attachment,
+    config: &ArchiveConfig,
+) -> Result<(Vec<u8>, String), ArchiveError> {
+    if attachment.size as u64 > config.max_bytes {
+        return Err(ArchiveError::Rejected(format!(
+            "Attachment exceeds {} bytes",
+            config.max_bytes
+        )));
+    }
+
+    let url = reqwest::Url::parse(&attachment.url)
+        .map_err(|e| ArchiveError::Rejected(format!("Invalid URL: {}", e)))?;
+    if !is_discord_cdn(&url) {
+        return Err(ArchiveError::Rejected(
+            "Only Discord CDN URLs are archived".to_string(),
+        ));
+    }
+
+    let mut response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| ArchiveError::Failed(format!("Download failed: {}", e)))?;
+
+    if response.status().is_redirection() {
+        return Err(ArchiveError::Rejected(format!(
+            "CDN redirected the download ({})",
+            response.status()
+        )));
+    }
+
+    // Only the CDN's content type is trusted; the client-reported one is often just "unknown"
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim().to_ascii_lowercase())
+        .unwrap_or_default();
+
+    if !config.is_allowed_type(&content_type) {
+        return Err(ArchiveError::Rejected(format!(
+            "Content type {} is not allowed",
+            content_type
+        )));
+    }
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| ArchiveError::Failed(format!("Download failed: {}", e)))?
+    {
+        if (bytes.len() + chunk.len()) as u64 > config.max_bytes {
+            return Err(ArchiveError::Rejected(format!(
+                "Attachment exceeds {} bytes",
+                config.max_bytes
+            )));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+
+    Ok((bytes, content_type))
+}
+
+async fn archive_attachment(
+    pool: &PgPool,
+    client: &reqwest::Client,
+    store: &dyn AttachmentStore,
+    config: &ArchiveConfig,
+    attachment: &db::Attachment,
+) -> Result<(), ArchiveError> {
+    let (bytes, content_type) = download(client, attachment, config).await?;
+    let checksum = hex::encode(Sha256::digest(&bytes));
+    let storage_key = attachment.id.to_string();
+
+    store
+        .put(&storage_key, &bytes)
+        .await
+        .map_err(|e| ArchiveError::Failed(format!("Storage write failed: {}", e)))?;
+
+    let update_result = sqlx::query(
+        r#"
+        UPDATE attachments
+        SET storage_key = $2, checksum_sha256 = $3, size = $4, archived_content_type = $5,
+            archived_at = NOW(), archive_error = NULL
+        WHERE id = $1
+        "#,
+    )
+    .bind(attachment.id)
+    .bind(&storage_key)
+    .bind(&checksum)
+    .bind(bytes.len() as i64)
+    .bind(&content_type)
+    .execute(pool)
+    .await;
+
+    // The row may have been deleted (purge, retention) while the file was downloading
+    match update_result {
+        Ok(result) if result.rows_affected() > 0 => Ok(()),
+        Ok(_) => {
+            storage::delete_keys(store, &[storage_key]).await;
+            Ok(())
+        }
+        Err(e) => {
+            storage::delete_keys(store, &[storage_key]).await;
+            Err(ArchiveError::Failed(format!("Database error: {}", e)))
+        }
+    }
+}
+
+// Function to archive newly referenced attachments in background
+pub async fn archive_pending_attachments(
+    pool: &PgPool,
+    client: &reqwest::Client,
+    store: &dyn AttachmentStore,
+    config: &ArchiveConfig,
+) {
+    let pending_result = sqlx::query_as::<_, db::Attachment>(
+        r#"
+        SELECT * FROM attachments
+        WHERE storage_key IS NULL AND archive_attempts < $1
+        ORDER BY created_at ASC
+        LIMIT $2
+        "#,
+    )
+    .bind(MAX_ARCHIVE_ATTEMPTS)
+    .bind(ARCHIVE_BATCH_SIZE)
+    .fetch_all(pool)
+    .await;
+
+    let pending = match pending_result {
+        Ok(pending) => pending,
+        Err(e) => {
+            eprintln!("Background attachment archive query failed: {}", e);
+            return;
+        }
+    };
+
+    for attachment in pending {
+        let (reason, attempts) =
+            match archive_attachment(pool, client, store, config, &attachment).await {
+                Ok(()) => continue,
+                Err(ArchiveError::Rejected(reason)) => (reason, MAX_ARCHIVE_ATTEMPTS),
+                Err(ArchiveError::Failed(reason)) => (reason, attachment.archive_attempts + 1),
+            };
+
+        eprintln!("Failed to archive attachment {}: {}", attachment.id, reason);
+
+        if let Err(e) = sqlx::query(
+            "UPDATE attachments SET archive_attempts = $2, archive_error = $3 WHERE id = $1",
+        )
+        .bind(attachment.id)
+        .bind(attempts)
+        .bind(&reason)
+        .execute(pool)
+        .await
+        {
+            eprintln!(
+                "Failed to record archive failure for attachment {}: {}",
+                attachment.id, e
+            );
+        }
+    }
+}
@@ -0,0 +1,277 @@
+use crate::db;
+use crate::structs::{AssignThread, UnassignThread};
+use actix_web::{get, post, web, HttpResponse, Responder, Result};
+use sqlx::{PgPool, Postgres, Transaction};
+
+// Marks the thread's current assignment row as ended; no-op when nobody holds it
+async fn end_current_assignment(
+    tx: &mut Transaction<'_, Postgres>,
+    thread_id: i32,
+    ended_by_id: Option<&str>,
+    ended_by_tag: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE thread_assignments
+        SET unassigned_at = NOW(), unassigned_by_id = $2, unassigned_by_tag = $3
+        WHERE thread_id = $1 AND unassigned_at IS NULL
+        "#,
+    )
+    .bind(thread_id)
+    .bind(ended_by_id)
+    .bind(ended_by_tag)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+#[get("/threads/{id}/assignments")]
+async fn get_thread_assignments(
+    pool: web::Data<PgPool>,
+    thread_id: web::Path<i32>,
+) -> impl Responder {
+    let assignments_result = sqlx::query_as::<_, db::ThreadAssignment>(
+        "SELECT * FROM thread_assignments WHERE thread_id = $1 ORDER BY assigned_at ASC, id ASC",
+    )
+    .bind(thread_id.into_inner())
+    .fetch_all(pool.get_ref())
+    .await;
+
+    match assignments_result {
+        Ok(assignments) => HttpResponse::Ok().json(assignments),
+        Err(e) => {
+            eprintln!("Database error fetching thread assignments: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch thread assignments"
+            }))
+        }
+    }
+}
+
+#[post("/threads/{id}/assign")]
+async fn assign_thread(
+    pool: web::Data<PgPool>,
+    thread_id: web::Path<i32>,
+    assignment: web::Json<AssignThread>,
+) -> Result<impl Responder> {
+    let thread_id = thread_id.into_inner();
+
+    // Validate assignee ID format (Discord IDs are numeric)
+    if !assignment.assignee_id.chars().all(|c| c.is_ascii_digit()) {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Invalid assignee ID format"
+        })));
+    }
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Database error starting transaction: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to assign thread"
+            })));
+        }
+    };
+
+    let thread_result =
+        sqlx::query_as::<_, db::Thread>("SELECT * FROM threads WHERE id = $1 FOR UPDATE")
+            .bind(thread_id)
+            .fetch_one(&mut *tx)
+            .await;
+
+    let thread = match thread_result {
+        Ok(thread) => thread,
+        Err(sqlx::Error::RowNotFound) => {
+            return Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Thread not found"
+            })));
+        }
+        Err(e) => {
+            eprintln!("Database error fetching thread: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch thread"
+            })));
+        }
+    };
+
+    if !thread.is_open {
+        return Ok(HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Cannot assign a closed thread"
+        })));
+    }
+
+    match thread.assigned_to_id.as_deref() {
+        // Claiming a thread you already own is a no-op
+        Some(current) if current == assignment.assignee_id => {
+            return Ok(HttpResponse::Ok().json(thread));
+        }
+        Some(_) if !assignment.force.unwrap_or(false) => {
+            return Ok(HttpResponse::Conflict().json(serde_json::json!({
+                "error": "Thread is already assigned to another moderator",
+                "assigned_to_id": thread.assigned_to_id,
+                "assigned_to_tag": thread.assigned_to_tag
+            })));
+        }
+        _ => {}
+    }
+
+    if let Err(e) = end_current_assignment(
+        &mut tx,
+        thread_id,
+        assignment.assigned_by_id.as_deref(),
+        assignment.assigned_by_tag.as_deref(),
+    )
+    .await
+    {
+        eprintln!("Database error ending previous assignment: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to assign thread"
+        })));
+    }
+
+    let history_result = sqlx::query(
+        r#"
+        INSERT INTO thread_assignments (thread_id, assignee_id, assignee_tag, assigned_by_id, assigned_by_tag)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+    )
+    .bind(thread_id)
+    .bind(&assignment.assignee_id)
+    .bind(&assignment.assignee_tag)
+    .bind(&assignment.assigned_by_id)
+    .bind(&assignment.assigned_by_tag)
+    .execute(&mut *tx)
+    .await;
+
+    if let Err(e) = history_result {
+        eprintln!("Database error recording assignment: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to assign thread"
+        })));
+    }
+
+    let updated_thread_result = sqlx::query_as::<_, db::Thread>(
+        r#"
+        UPDATE threads
+        SET assigned_to_id = $2, assigned_to_tag = $3, assigned_at = NOW()
+        WHERE id = $1
+        RETURNING *
+        "#,
+    )
+    .bind(thread_id)
+    .bind(&assignment.assignee_id)
+    .bind(&assignment.assignee_tag)
+    .fetch_one(&mut *tx)
+    .await;
+
+    let updated_thread = match updated_thread_result {
+        Ok(thread) => thread,
+        Err(e) => {
+            eprintln!("Database error assigning thread: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to assign thread"
+            })));
+        }
+    };
+
+    if let Err(e) = tx.commit().await {
+        eprintln!("Database error committing thread assignment: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to assign thread"
+        })));
+    }
+
+    Ok(HttpResponse::Ok().json(updated_thread))
+}
+
+#[post("/threads/{id}/unassign")]
+async fn unassign_thread(
+    pool: web::Data<PgPool>,
+    thread_id: web::Path<i32>,
+    unassign_data: Option<web::Json<UnassignThread>>,
+) -> Result<impl Responder> {
+    let thread_id = thread_id.into_inner();
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Database error starting transaction: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to unassign thread"
+            })));
+        }
+    };
+
+    let thread_result =
+        sqlx::query_as::<_, db::Thread>("SELECT * FROM threads WHERE id = $1 FOR UPDATE")
+            .bind(thread_id)
+            .fetch_one(&mut *tx)
+            .await;
+
+    let thread = match thread_result {
+        Ok(thread) => thread,
+        Err(sqlx::Error::RowNotFound) => {
+            return Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Thread not found"
+            })));
+        }
+        Err(e) => {
+            eprintln!("Database error fetching thread: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch thread"
+            })));
+        }
+    };
+
+    if thread.assigned_to_id.is_none() {
+        return Ok(HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Thread is not assigned"
+        })));
+    }
+
+    if let Err(e) = end_current_assignment(
+        &mut tx,
+        thread_id,
+        unassign_data.as_ref().map(|u| u.unassigned_by_id.as_str()),
+        unassign_data.as_ref().map(|u| u.unassigned_by_tag.as_str()),
+    )
+    .await
+    {
+        eprintln!("Database error ending assignment: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to unassign thread"
+        })));
+    }
+
+    let updated_thread_result = sqlx::query_as::<_, db::Thread>(
+        r#"
+        UPDATE threads
+        SET assigned_to_id = NULL, assigned_to_tag = NULL, assigned_at = NULL
+        WHERE id = $1
+        RETURNING *
+        "#,
+    )
+    .bind(thread_id)
+    .fetch_one(&mut *tx)
+    .await;
+
+    let updated_thread = match updated_thread_result {
+        Ok(thread) => thread,
+        Err(e) => {
+            eprintln!("Database error unassigning thread: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to unassign thread"
+            })));
+        }
+    };
+
+    if let Err(e) = tx.commit().await {
+        eprintln!("Database error committing thread unassignment: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to unassign thread"
+        })));
+    }
+
+    Ok(HttpResponse::Ok().json(updated_thread))
+}
@@ -0,0 +1,313 @@
+use crate::archiver;
+use crate::db;
+use crate::storage::AttachmentStore;
+use crate::structs::CreateAttachment;
+use actix_web::{get, http::header, web, HttpResponse, Responder};
+use sqlx::{PgConnection, PgExecutor, PgPool};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const MAX_ATTACHMENTS_PER_MESSAGE: usize = 10; // Discord's own limit
+
+fn attachment_error(message: &str) -> HttpResponse {
+    HttpResponse::BadRequest().json(serde_json::json!({
+        "error": message
+    }))
+}
+
+// Anything else (javascript:, data:, ...) must never end up as a link
+pub fn is_web_url(url: &str) -> bool {
+    url.starts_with("https://") || url.starts_with("http://")
+}
+
+pub fn validate_attachments(attachments: &[CreateAttachment]) -> Result<(), HttpResponse> {
+    if attachments.len() > MAX_ATTACHMENTS_PER_MESSAGE {
+        return Err(attachment_error(
+            "Too many attachments. Maximum is 10 per message",
+        ));
+    }
+
+    for attachment in attachments {
+        if !is_web_url(&attachment.url) {
+            return Err(attachment_error("Attachment URL must be http or https"));
+        }
+        if attachment.filename.trim().is_empty() || attachment.filename.len() > 255 {
+            return Err(attachment_error(
+                "Attachment filename must be between 1 and 255 characters",
+            ));
+        }
+        if attachment.size < 0 {
+            return Err(attachment_error("Attachment size cannot be negative"));
+        }
+        if attachment
+            .content_type
+            .as_ref()
+            .is_some_and(|content_type| content_type.len() > 255)
+        {
+            return Err(attachment_error("Attachment content type is too long"));
+        }
+        // Validate Discord attachment ID format (Discord IDs are numeric)
+        if attachment
+            .discord_id
+            .as_ref()
+            .is_some_and(|id| !id.chars().all(|c| c.is_ascii_digit()))
+        {
+            return Err(attachment_error("Invalid attachment Discord ID format"));
+        }
+    }
+
+    Ok(())
+}
+
+// Writes a message's attachments in the order they were sent
+pub async fn insert_attachments(
+    conn: &mut PgConnection,
+    message_id: Uuid,
+    attachments: &[CreateAttachment],
+) -> Result<Vec<db::Attachment>, sqlx::Error> {
+    let mut inserted = Vec::with_capacity(attachments.len());
+
+    for (position, attachment) in attachments.iter().enumerate() {
+        let row = sqlx::query_as::<_, db::Attachment>(
+            r#"
+            INSERT INTO attachments (message_id, discord_id, url, filename, content_type, size, position)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING *
+            "#,
+        )
+        .bind(message_id)
+        .bind(&attachment.discord_id)
+        .bind(&attachment.url)
+        .bind(attachment.filename.trim())
+        .bind(&attachment.content_type)
+        .bind(attachment.size)
+        .bind(position as i32)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        inserted.push(row);
+    }
+
+    Ok(inserted)
+}
+
+// Swaps a message's attachment rows for a new list. Also returns the storage keys of archived
+// files that belonged to the old rows so the caller can delete them once the transaction commits.
+pub async fn replace_attachments(
+    conn: &mut PgConnection,
+    message_id: Uuid,
+    attachments: &[CreateAttachment],
+) -> Result<(Vec<db::Attachment>, Vec<String>), sqlx::Error> {
+    let storage_keys: Vec<Option<String>> =
+        sqlx::query_scalar("DELETE FROM attachments WHERE message_id = $1 RETURNING storage_key")
+            .bind(message_id)
+            .fetch_all(&mut *conn)
+            .await?;
+
+    let inserted = insert_attachments(&mut *conn, message_id, attachments).await?;
+
+    Ok((inserted, storage_keys.into_iter().flatten().collect()))
+}
+
+// Whether an edit leaves a message's attachments as they are stored
+pub fn same_attachments(stored: &[db::Attachment], submitted: &[CreateAttachment]) -> bool {
+    stored.len() == submitted.len()
+        && stored.iter().zip(submitted).all(|(stored, submitted)| {
+            stored.url == submitted.url
+                && stored.filename == submitted.filename.trim()
+                && stored.content_type == submitted.content_type
+                && stored.size == submitted.size
+                && stored.discord_id == submitted.discord_id
+        })
+}
+
+// The submitted form of stored attachments, as kept in message revisions
+pub fn as_submitted(stored: &[db::Attachment]) -> Vec<CreateAttachment> {
+    stored
+        .iter()
+        .map(|attachment| CreateAttachment {
+            url: attachment.url.clone(),
+            filename: attachment.filename.clone(),
+            content_type: attachment.content_type.clone(),
+            size: attachment.size,
+            discord_id: attachment.discord_id.clone(),
+        })
+        .collect()
+}
+
+// Attachments of the given messages, grouped by message in the order they were sent
+pub async fn fetch_by_message<'e, E: PgExecutor<'e>>(
+    executor: E,
+    message_ids: &[Uuid],
+) -> Result<HashMap<Uuid, Vec<db::Attachment>>, sqlx::Error> {
+    let attachments = sqlx::query_as::<_, db::Attachment>(
+        "SELECT * FROM attachments WHERE message_id = ANY($1) ORDER BY message_id, position ASC",
+    )
+    .bind(message_ids)
+    .fetch_all(executor)
+    .await?;
+
+    let mut by_message: HashMap<Uuid, Vec<db::Attachment>> = HashMap::new();
+    for attachment in attachments {
+        by_message
+            .entry(attachment.message_id)
+            .or_default()
+            .push(attachment);
+    }
+    Ok(by_message)
+}
+
+// Fills in Message::attachments, which isn't a column of messages
+pub async fn attach_to_messages<'e, E: PgExecutor<'e>>(
+    executor: E,
+    messages: &mut [db::Message],
+) -> Result<(), sqlx::Error> {
+    let message_ids: Vec<Uuid> = messages.iter().map(|message| message.id).collect();
+    let mut by_message = fetch_by_message(executor, &message_ids).await?;
+    for message in messages {
+        message.attachments = by_message.remove(&message.id).unwrap_or_default();
+    }
+    Ok(())
+}
+
+#[get("/threads/{id}/attachments")]
+async fn get_thread_attachments(
+    pool: web::Data<PgPool>,
+    thread_id: web::Path<i32>,
+) -> impl Responder {
+    let thread_id = thread_id.into_inner();
+
+    let thread_exists_result: Result<bool, sqlx::Error> =
+        sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM threads WHERE id = $1)")
+            .bind(thread_id)
+            .fetch_one(pool.get_ref())
+            .await;
+
+    match thread_exists_result {
+        Ok(true) => {}
+        Ok(false) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Thread not found"
+            }));
+        }
+        Err(e) => {
+            eprintln!("Database error fetching thread: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch thread"
+            }));
+        }
+    }
+
+    let attachments_result = sqlx::query_as::<_, db::Attachment>(
+        r#"
+        SELECT a.*
+        FROM attachments a
+        INNER JOIN thread_messages tm ON tm.message_id = a.message_id
+        INNER JOIN messages m ON m.id = a.message_id
+        WHERE tm.thread_id = $1
+        ORDER BY m.created_at ASC, m.id ASC, a.position ASC
+        "#,
+    )
+    .bind(thread_id)
+    .fetch_all(pool.get_ref())
+    .await;
+
+    match attachments_result {
+        Ok(attachments) => HttpResponse::Ok().json(attachments),
+        Err(e) => {
+            eprintln!("Database error fetching thread attachments: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch thread attachments"
+            }))
+        }
+    }
+}
+
+// Only media renders inline; everything else downloads so it can't run in our origin.
+// SVG is an image but can carry scripts, so it always downloads.
+pub fn is_inline_type(content_type: &str) -> bool {
+    let content_type = content_type.to_ascii_lowercase();
+    ["image/", "video/", "audio/"]
+        .iter()
+        .any(|prefix| content_type.starts_with(prefix))
+        && !content_type.starts_with("image/svg")
+}
+
+#[get("/attachments/{id}")]
+async fn get_attachment(
+    pool: web::Data<PgPool>,
+    store: Option<web::Data<dyn AttachmentStore>>,
+    attachment_id: web::Path<Uuid>,
+) -> impl Responder {
+    let attachment_result =
+        sqlx::query_as::<_, db::Attachment>("SELECT * FROM attachments WHERE id = $1")
+            .bind(attachment_id.into_inner())
+            .fetch_one(pool.get_ref())
+            .await;
+
+    let attachment = match attachment_result {
+        Ok(attachment) => attachment,
+        Err(sqlx::Error::RowNotFound) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Attachment not found"
+            }));
+        }
+        Err(e) => {
+            eprintln!("Database error fetching attachment: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch attachment"
+            }));
+        }
+    };
+
+    if let (Some(store), Some(storage_key)) = (store, &attachment.storage_key) {
+        match store.get(storage_key).await {
+            Ok(Some(bytes)) => {
+                // Serve the type the CDN reported at archive time, never the client-reported one
+                let content_type = attachment
+                    .archived_content_type
+                    .clone()
+                    .unwrap_or_else(|| "application/octet-stream".to_string());
+                let disposition = if is_inline_type(&content_type) {
+                    "inline"
+                } else {
+                    "attachment"
+                };
+                let filename = attachment.filename.replace(['"', '\\', '\r', '\n'], "_");
+
+                let mut response = HttpResponse::Ok();
+                response
+                    .content_type(content_type)
+                    .insert_header((header::X_CONTENT_TYPE_OPTIONS, "nosniff"))
+                    .insert_header((header::CONTENT_SECURITY_POLICY, "sandbox"))
+                    .insert_header((
+                        header::CONTENT_DISPOSITION,
+                        format!("{}; filename=\"{}\"", disposition, filename),
+                    ));
+                if let Some(checksum) = &attachment.checksum_sha256 {
+                    response.insert_header((header::ETAG, format!("\"{}\"", checksum)));
+                }
+                return response.body(bytes);
+            }
+            Ok(None) => {
+                eprintln!(
+                    "Archived attachment {} is missing from storage",
+                    attachment.id
+                );
+            }
+            Err(e) => {
+                eprintln!("Storage error reading attachment {}: {}", attachment.id, e);
+            }
+        }
+    }
+
+    // Not archived (yet): fall back to the original URL, but never send anyone off Discord's CDN
+    match reqwest::Url::parse(&attachment.url) {
+        Ok(url) if archiver::is_discord_cdn(&url) => HttpResponse::Found()
+            .insert_header((header::LOCATION, url.as_str()))
+            .finish(),
+        _ => HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Attachment is not available"
+        })),
+    }
+}
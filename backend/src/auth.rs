@@ -0,0 +1,20 @@
+use actix_web::HttpRequest;
+
+// Admin-only endpoints require `Authorization: Bearer <ADMIN_API_TOKEN>`.
+// When ADMIN_API_TOKEN is unset, nobody is an admin.
+pub fn is_admin(req: &HttpRequest) -> bool {
+    let expected = match std::env::var("ADMIN_API_TOKEN") {
+        Ok(token) if !token.is_empty() => token,
+        _ => return false,
+    };
+
+    req.headers()
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|token| constant_time_eq(token.as_bytes(), expected.as_bytes()))
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -0,0 +1,148 @@
+use crate::db;
+use crate::threads;
+use sqlx::PgPool;
+use std::env;
+
+pub const SYSTEM_ACTOR_ID: &str = "system";
+pub const SYSTEM_ACTOR_TAG: &str = "System";
+
+pub struct AutoCloseConfig {
+    pub inactive_hours: i32,
+    pub only_after_staff_reply: bool,
+    pub interval_secs: u64,
+}
+
+impl AutoCloseConfig {
+    // Auto-close is off unless AUTO_CLOSE_INACTIVE_HOURS is set to a positive number
+    pub fn from_env() -> Option<Self> {
+        let inactive_hours = env::var("AUTO_CLOSE_INACTIVE_HOURS")
+            .ok()?
+            .parse::<i32>()
+            .ok()
+            .filter(|hours| *hours > 0)?;
+
+        let only_after_staff_reply = env::var("AUTO_CLOSE_ONLY_AFTER_STAFF_REPLY")
+            .map(|v| v == "true" || v == "1")
+            .unwrap_or(false);
+
+        let interval_secs = env::var("AUTO_CLOSE_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|secs| *secs > 0)
+            .unwrap_or(900); // Check every 15 minutes
+
+        Some(Self {
+            inactive_hours,
+            only_after_staff_reply,
+            interval_secs,
+        })
+    }
+}
+
+// Open threads whose last message is older than $1 hours. Threads on hold are parked
+// deliberately and never auto-close.
+const INACTIVE_THREADS: &str = r#"
+    SELECT t.id
+    FROM threads t
+    LEFT JOIN LATERAL (
+        SELECT m.kind, m.created_at
+        FROM thread_messages tm
+        JOIN messages m ON tm.message_id = m.id
+        WHERE tm.thread_id = t.id
+        ORDER BY m.created_at DESC, m.id DESC
+        LIMIT 1
+    ) last_message ON TRUE
+    WHERE t.is_open = TRUE
+    AND t.status <> 'on_hold'
+    AND COALESCE(last_message.created_at, t.created_at) < NOW() - make_interval(hours => $1)
+    AND (NOT $2 OR last_message.kind IN ('staff_reply', 'staff_anonymous_reply', 'macro'))
+"#;
+
+// Rechecks the thread under its row lock, since a reply, status change or manual close may
+// have landed after the candidate query ran. Returns the thread as it was before closing.
+async fn close_if_inactive(
+    pool: &PgPool,
+    thread_id: i32,
+    config: &AutoCloseConfig,
+    reason: &str,
+) -> Result<Option<db::Thread>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let thread = sqlx::query_as::<_, db::Thread>("SELECT * FROM threads WHERE id = $1 FOR UPDATE")
+        .bind(thread_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+    let thread = match thread {
+        Some(thread) => thread,
+        None => return Ok(None),
+    };
+
+    let still_inactive: Option<i32> =
+        sqlx::query_scalar(&format!("{} AND t.id = $3", INACTIVE_THREADS))
+            .bind(config.inactive_hours)
+            .bind(config.only_after_staff_reply)
+            .bind(thread_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+    if still_inactive.is_none() {
+        return Ok(None);
+    }
+
+    threads::close_locked_thread(
+        &mut tx,
+        thread_id,
+        Some(SYSTEM_ACTOR_ID),
+        Some(SYSTEM_ACTOR_TAG),
+        Some(reason),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Some(thread))
+}
+
+// Function to close inactive threads in background
+pub async fn close_inactive_threads(pool: &PgPool, config: &AutoCloseConfig) {
+    let candidates_result: Result<Vec<i32>, sqlx::Error> =
+        sqlx::query_scalar(&format!("{} ORDER BY t.id", INACTIVE_THREADS))
+            .bind(config.inactive_hours)
+            .bind(config.only_after_staff_reply)
+            .fetch_all(pool)
+            .await;
+
+    let candidates = match candidates_result {
+        Ok(ids) => ids,
+        Err(e) => {
+            eprintln!("Background auto-close query failed: {}", e);
+            return;
+        }
+    };
+
+    let reason = format!(
+        "Closed automatically after {} hours of inactivity",
+        config.inactive_hours
+    );
+    let mut closed = 0;
+
+    for thread_id in candidates {
+        match close_if_inactive(pool, thread_id, config, &reason).await {
+            Ok(Some(thread)) => {
+                threads::send_thread_closed_webhook(&thread, SYSTEM_ACTOR_ID, SYSTEM_ACTOR_TAG);
+                closed += 1;
+            }
+            // Closed, replied to or put on hold between the query and now
+            Ok(None) => {}
+            Err(e) => {
+                eprintln!(
+                    "Background auto-close of thread {} failed: {}",
+                    thread_id, e
+                );
+            }
+        }
+    }
+
+    if closed > 0 {
+        println!("Background auto-close closed {} inactive threads", closed);
+    }
+}
@@ -8,7 +8,47 @@ pub struct Message {
     pub author_id: String,
     pub author_tag: String,
     pub content: String,
+    // Loaded from the attachments table, see attachments::attach_to_messages
+    #[sqlx(skip)]
+    pub attachments: Vec<Attachment>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub kind: String,
+    pub discord_message_id: Option<String>,
+    #[serde(with = "chrono::serde::ts_seconds_option")]
+    pub edited_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(with = "chrono::serde::ts_seconds_option")]
+    pub deleted_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(sqlx::FromRow, Serialize, Deserialize)]
+pub struct Attachment {
+    pub id: Uuid,
+    pub message_id: Uuid,
+    pub discord_id: Option<String>,
+    pub url: String,
+    pub filename: String,
+    pub content_type: Option<String>,
+    pub size: i64,
+    pub position: i32,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub storage_key: Option<String>,
+    pub checksum_sha256: Option<String>,
+    #[serde(with = "chrono::serde::ts_seconds_option")]
+    pub archived_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub archive_attempts: i32,
+    pub archive_error: Option<String>,
+    pub archived_content_type: Option<String>,
+}
+
+#[derive(sqlx::FromRow, Serialize, Deserialize)]
+pub struct MessageRevision {
+    pub id: i32,
+    pub message_id: Uuid,
+    pub content: String,
     pub attachments: serde_json::Value,
+    pub edited_by_id: Option<String>,
     #[serde(with = "chrono::serde::ts_seconds")]
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
@@ -19,11 +59,71 @@ pub struct Thread {
     pub user_id: String,
     pub thread_id: String,
     pub is_open: bool,
+    pub status: String,
     pub urgency: String,
     #[serde(with = "chrono::serde::ts_seconds_option")]
     pub created_at: Option<chrono::DateTime<chrono::Utc>>,
     #[serde(with = "chrono::serde::ts_seconds_option")]
     pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(with = "chrono::serde::ts_seconds_option")]
+    pub closed_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub closed_by_id: Option<String>,
+    pub closed_by_tag: Option<String>,
+    pub close_reason: Option<String>,
+    pub assigned_to_id: Option<String>,
+    pub assigned_to_tag: Option<String>,
+    #[serde(with = "chrono::serde::ts_seconds_option")]
+    pub assigned_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(sqlx::FromRow, Serialize, Deserialize)]
+pub struct ThreadEvent {
+    pub id: i32,
+    pub thread_id: i32,
+    pub event_type: String,
+    pub actor_id: Option<String>,
+    pub actor_tag: Option<String>,
+    pub reason: Option<String>,
+    pub metadata: serde_json::Value,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(sqlx::FromRow, Serialize, Deserialize)]
+pub struct ThreadAssignment {
+    pub id: i32,
+    pub thread_id: i32,
+    pub assignee_id: String,
+    pub assignee_tag: String,
+    pub assigned_by_id: Option<String>,
+    pub assigned_by_tag: Option<String>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub assigned_at: chrono::DateTime<chrono::Utc>,
+    pub unassigned_by_id: Option<String>,
+    pub unassigned_by_tag: Option<String>,
+    #[serde(with = "chrono::serde::ts_seconds_option")]
+    pub unassigned_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(sqlx::FromRow, Serialize, Deserialize)]
+pub struct ScheduledClose {
+    pub thread_id: i32,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub close_at: chrono::DateTime<chrono::Utc>,
+    pub scheduled_by_id: Option<String>,
+    pub scheduled_by_tag: Option<String>,
+    pub reason: Option<String>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(sqlx::FromRow, Serialize, Deserialize)]
+pub struct Tag {
+    pub id: i32,
+    pub name: String,
+    pub color: Option<String>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
 #[derive(sqlx::FromRow, Serialize, Deserialize)]
@@ -43,6 +143,21 @@ pub struct Note {
     pub content: String,
     #[serde(with = "chrono::serde::ts_seconds")]
     pub created_at: chrono::DateTime<chrono::Utc>,
+    pub pinned: bool,
+    #[serde(with = "chrono::serde::ts_seconds_option")]
+    pub edited_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(with = "chrono::serde::ts_seconds_option")]
+    pub deleted_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(sqlx::FromRow, Serialize, Deserialize)]
+pub struct NoteRevision {
+    pub id: i32,
+    pub note_id: Uuid,
+    pub content: String,
+    pub edited_by_id: Option<String>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
 #[derive(sqlx::FromRow, Serialize, Deserialize)]
@@ -57,6 +172,18 @@ pub struct BlockedUser {
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
+#[derive(sqlx::FromRow, Serialize, Deserialize)]
+pub struct UserAnonymization {
+    pub id: i32,
+    pub user_id: String,
+    pub requested_by_id: String,
+    pub requested_by_tag: String,
+    pub reason: Option<String>,
+    pub counts: serde_json::Value,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
 pub async fn connect(database_url: &str) -> Result<Pool<Postgres>, sqlx::Error> {
     PgPoolOptions::new()
         .max_connections(20)
@@ -0,0 +1,265 @@
+use crate::attachments;
+use crate::auth;
+use crate::db;
+use crate::storage::AttachmentStore;
+use crate::transcripts;
+use actix_web::{get, http::header, web, HttpRequest, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::io::{Cursor, Write};
+use zip::write::FileOptions;
+
+#[derive(Deserialize)]
+struct ExportQuery {
+    user_facing: Option<bool>,
+}
+
+#[derive(sqlx::FromRow, Serialize)]
+struct AuthoredMessage {
+    thread_id: Option<i32>,
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    message: db::Message,
+}
+
+#[derive(Serialize)]
+struct UserExport {
+    user_id: String,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    generated_at: chrono::DateTime<chrono::Utc>,
+    user_facing: bool,
+    threads: Vec<transcripts::Transcript>,
+    // Messages the user wrote in someone else's thread (e.g. when the user is also staff) or
+    // outside any thread
+    authored_messages: Vec<AuthoredMessage>,
+    message_revisions: Vec<db::MessageRevision>,
+    notes: Vec<db::Note>,
+    note_revisions: Vec<db::NoteRevision>,
+    blocks: Vec<db::BlockedUser>,
+}
+
+impl UserExport {
+    fn is_empty(&self) -> bool {
+        self.threads.is_empty()
+            && self.authored_messages.is_empty()
+            && self.message_revisions.is_empty()
+            && self.notes.is_empty()
+            && self.note_revisions.is_empty()
+            && self.blocks.is_empty()
+    }
+}
+
+async fn load_user_export(
+    pool: &PgPool,
+    user_id: &str,
+    user_facing: bool,
+) -> Result<UserExport, sqlx::Error> {
+    let thread_ids: Vec<i32> =
+        sqlx::query_scalar("SELECT id FROM threads WHERE user_id = $1 ORDER BY id ASC")
+            .bind(user_id)
+            .fetch_all(pool)
+            .await?;
+
+    // A user-facing export never reveals who sent anonymous replies, which staff acted on the
+    // thread or what they noted down. The admin export is a full record, so it keeps soft-deleted
+    // notes too.
+    let mut threads = Vec::with_capacity(thread_ids.len());
+    for thread_id in thread_ids {
+        if let Some(mut transcript) =
+            transcripts::load_transcript(pool, thread_id, !user_facing, !user_facing, !user_facing)
+                .await?
+        {
+            if user_facing {
+                transcript.hide_staff_details();
+            }
+            threads.push(transcript);
+        }
+    }
+
+    // Includes messages that never joined a thread (sent through POST /messages)
+    let mut authored_messages = sqlx::query_as::<_, AuthoredMessage>(
+        r#"
+        SELECT tm.thread_id, m.* FROM messages m
+        LEFT JOIN thread_messages tm ON m.id = tm.message_id
+        LEFT JOIN threads t ON t.id = tm.thread_id
+        WHERE m.author_id = $1 AND (t.id IS NULL OR t.user_id <> $1)
+        ORDER BY m.created_at ASC, m.id ASC
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    let message_ids: Vec<_> = authored_messages.iter().map(|m| m.message.id).collect();
+    let mut attachments_by_message = attachments::fetch_by_message(pool, &message_ids).await?;
+    for authored in &mut authored_messages {
+        authored.message.attachments = attachments_by_message
+            .remove(&authored.message.id)
+            .unwrap_or_default();
+    }
+
+    let message_revisions = sqlx::query_as::<_, db::MessageRevision>(
+        r#"
+        SELECT r.* FROM message_revisions r
+        JOIN messages m ON m.id = r.message_id
+        WHERE m.author_id = $1
+        ORDER BY r.created_at ASC, r.id ASC
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    // Notes on the user's own threads are already part of their transcripts
+    let notes = if user_facing {
+        Vec::new()
+    } else {
+        sqlx::query_as::<_, db::Note>(
+            r#"
+            SELECT n.* FROM notes n
+            JOIN threads t ON t.id = n.thread_id
+            WHERE n.author_id = $1 AND t.user_id <> $1
+            ORDER BY n.created_at ASC, n.id ASC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?
+    };
+
+    // Earlier versions of the notes above and of notes on the user's own threads
+    let note_revisions = if user_facing {
+        Vec::new()
+    } else {
+        sqlx::query_as::<_, db::NoteRevision>(
+            r#"
+            SELECT r.* FROM note_revisions r
+            JOIN notes n ON n.id = r.note_id
+            JOIN threads t ON t.id = n.thread_id
+            WHERE n.author_id = $1 OR t.user_id = $1
+            ORDER BY r.created_at ASC, r.id ASC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?
+    };
+
+    // Blocks of the user and blocks the user placed as staff
+    let blocks = sqlx::query_as::<_, db::BlockedUser>(
+        "SELECT * FROM blocked_users WHERE user_id = $1 OR blocked_by = $1 ORDER BY created_at ASC",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(UserExport {
+        user_id: user_id.to_string(),
+        generated_at: chrono::Utc::now(),
+        user_facing,
+        threads,
+        authored_messages,
+        message_revisions,
+        notes,
+        note_revisions,
+        blocks,
+    })
+}
+
+async fn build_archive(
+    export: &UserExport,
+    store: Option<&dyn AttachmentStore>,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    // Render up front: the zip writer is synchronous and HTML rendering may read from storage
+    let mut files = vec![(
+        "export.json".to_string(),
+        serde_json::to_vec_pretty(export)?,
+    )];
+    for transcript in &export.threads {
+        let thread_id = transcript.thread.id;
+        files.push((
+            format!("transcripts/thread-{}.html", thread_id),
+            transcripts::render_html(transcript, store)
+                .await
+                .into_bytes(),
+        ));
+        files.push((
+            format!("transcripts/thread-{}.txt", thread_id),
+            transcripts::render_text(transcript).into_bytes(),
+        ));
+    }
+
+    let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options = FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    for (name, bytes) in files {
+        writer.start_file(name, options)?;
+        writer.write_all(&bytes)?;
+    }
+    Ok(writer.finish()?.into_inner())
+}
+
+#[get("/users/{user_id}/export")]
+async fn export_user_data(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    store: Option<web::Data<dyn AttachmentStore>>,
+    user_id: web::Path<String>,
+    query: web::Query<ExportQuery>,
+) -> impl Responder {
+    if !auth::is_admin(&req) {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Admin access required"
+        }));
+    }
+
+    let user_id = user_id.into_inner();
+
+    // Validate user ID format (Discord IDs are numeric)
+    if user_id.is_empty() || !user_id.chars().all(|c| c.is_ascii_digit()) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Invalid user ID format"
+        }));
+    }
+
+    let user_facing = query.user_facing.unwrap_or(false);
+    let export = match load_user_export(pool.get_ref(), &user_id, user_facing).await {
+        Ok(export) => export,
+        Err(e) => {
+            eprintln!("Database error exporting user data: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to export user data"
+            }));
+        }
+    };
+
+    if export.is_empty() {
+        return HttpResponse::NotFound().json(serde_json::json!({
+            "error": "No data found for user"
+        }));
+    }
+
+    let archive = match build_archive(&export, store.as_ref().map(|store| store.get_ref())).await {
+        Ok(archive) => archive,
+        Err(e) => {
+            eprintln!("Error building export archive for user {}: {}", user_id, e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to export user data"
+            }));
+        }
+    };
+
+    println!(
+        "Exported data for user {} ({} threads, user_facing={})",
+        user_id,
+        export.threads.len(),
+        user_facing
+    );
+
+    HttpResponse::Ok()
+        .content_type("application/zip")
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"user-{}-export.zip\"", user_id),
+        ))
+        .body(archive)
+}
@@ -4,13 +4,30 @@ use dotenv::dotenv;
 use std::env;
 
 mod analytics;
+mod anonymize;
+mod archiver;
+mod assignments;
+mod attachments;
+mod auth;
+mod auto_close;
 mod blocked_users;
 mod db;
+mod export;
 mod macros;
+mod merge;
 mod messages;
 mod notes;
+mod pagination;
+mod purge;
+mod retention;
+mod scheduled_close;
+mod search;
+mod storage;
 mod structs;
+mod tags;
+mod thread_events;
 mod threads;
+mod transcripts;
 
 use actix_web::{get, HttpResponse, Responder};
 
@@ -31,6 +48,15 @@ async fn main() -> std::io::Result<()> {
 
     // Clone pool for background task before moving into HttpServer
     let analytics_pool = pool.clone();
+    let auto_close_pool = pool.clone();
+    let scheduled_close_pool = pool.clone();
+    let archiver_pool = pool.clone();
+    let retention_pool = pool.clone();
+
+    let archive_config = archiver::ArchiveConfig::from_env();
+    let attachment_store = archive_config.as_ref().map(|config| config.store());
+    let archiver_store = attachment_store.clone();
+    let retention_store = attachment_store.clone();
 
     let server = HttpServer::new(move || {
         let cors = Cors::default()
@@ -40,20 +66,56 @@ async fn main() -> std::io::Result<()> {
             .supports_credentials()
             .max_age(3600);
 
-        App::new()
-            .wrap(cors)
-            .app_data(web::Data::new(pool.clone()))
-            .service(health_check)
+        let app = App::new().wrap(cors).app_data(web::Data::new(pool.clone()));
+
+        // Archived attachments can only be served when a storage backend is configured
+        let app = match &attachment_store {
+            Some(store) => app.app_data(web::Data::from(store.clone())),
+            None => app,
+        };
+
+        app.service(health_check)
+            .service(search::search)
             .service(messages::get_messages)
             .service(messages::create_message)
+            .service(messages::edit_message)
+            .service(messages::delete_message)
+            .service(messages::get_message_revisions)
             .service(threads::get_threads)
             .service(threads::create_thread)
+            .service(threads::get_thread_by_user)
+            .service(threads::get_thread_by_channel)
             .service(threads::get_thread)
+            .service(transcripts::get_thread_transcript)
+            .service(attachments::get_thread_attachments)
+            .service(attachments::get_attachment)
+            .service(purge::delete_thread)
             .service(threads::close_thread)
+            .service(threads::reopen_thread)
+            .service(merge::merge_thread)
+            .service(scheduled_close::cancel_scheduled_close)
+            .service(thread_events::get_thread_events)
+            .service(assignments::assign_thread)
+            .service(assignments::unassign_thread)
+            .service(assignments::get_thread_assignments)
             .service(threads::add_message_to_thread)
             .service(threads::update_thread_urgency)
+            .service(threads::update_thread_status)
+            .service(threads::update_thread_channel)
+            .service(tags::get_tags)
+            .service(tags::create_tag)
+            .service(tags::update_tag)
+            .service(tags::delete_tag)
+            .service(tags::get_thread_tags)
+            .service(tags::set_thread_tags)
             .service(notes::get_thread_notes)
             .service(notes::add_note_to_thread)
+            .service(notes::edit_note)
+            .service(notes::delete_note)
+            .service(notes::get_note_revisions)
+            .service(export::export_user_data)
+            .service(anonymize::anonymize_user)
+            .service(anonymize::get_user_anonymizations)
             .service(blocked_users::get_blocked_users)
             .service(blocked_users::block_user)
             .service(blocked_users::unblock_user)
@@ -68,7 +130,10 @@ async fn main() -> std::io::Result<()> {
             .service(analytics::get_thread_volume)
             .service(analytics::get_moderator_activity)
             .service(analytics::get_response_times)
+            .service(analytics::get_tag_breakdown)
+            .service(analytics::get_status_breakdown)
             .service(analytics::refresh_analytics) // Add new refresh endpoint
+            .service(retention::get_retention_runs)
     })
     .bind(("0.0.0.0", 8080))?
     .run();
@@ -82,5 +147,69 @@ async fn main() -> std::io::Result<()> {
         }
     });
 
+    // Start background task for executing due scheduled closes
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(
+            scheduled_close::interval_secs(),
+        ));
+        loop {
+            interval.tick().await;
+            scheduled_close::run_due_closes(&scheduled_close_pool).await;
+        }
+    });
+
+    // Start background task for closing inactive threads when a policy is configured
+    if let Some(auto_close_config) = auto_close::AutoCloseConfig::from_env() {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(
+                auto_close_config.interval_secs,
+            ));
+            loop {
+                interval.tick().await;
+                auto_close::close_inactive_threads(&auto_close_pool, &auto_close_config).await;
+            }
+        });
+    }
+
+    // Start background task for pruning expired data when a retention policy is configured
+    if let Some(retention_config) = retention::RetentionConfig::from_env() {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(
+                retention_config.interval_secs,
+            ));
+            loop {
+                interval.tick().await;
+                retention::run_retention(
+                    &retention_pool,
+                    retention_store.as_deref(),
+                    &retention_config,
+                )
+                .await;
+            }
+        });
+    }
+
+    // Start background task for archiving attachments when storage is configured
+    if let (Some(archive_config), Some(archiver_store)) = (archive_config, archiver_store) {
+        let client = archive_config
+            .client()
+            .expect("Failed to build attachment download client");
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(
+                archive_config.interval_secs,
+            ));
+            loop {
+                interval.tick().await;
+                archiver::archive_pending_attachments(
+                    &archiver_pool,
+                    &client,
+                    archiver_store.as_ref(),
+                    &archive_config,
+                )
+                .await;
+            }
+        });
+    }
+
     server.await
 }
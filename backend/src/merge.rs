@@ -0,0 +1,205 @@
+use crate::db;
+use crate::structs::MergeThread;
+use crate::thread_events;
+use crate::threads;
+use actix_web::{post, web, HttpResponse, Responder, Result};
+use sqlx::PgPool;
+
+#[post("/threads/{id}/merge")]
+async fn merge_thread(
+    pool: web::Data<PgPool>,
+    thread_id: web::Path<i32>,
+    merge_data: web::Json<MergeThread>,
+) -> Result<impl Responder> {
+    let target_id = thread_id.into_inner();
+    let source_id = merge_data.source_thread_id;
+
+    if source_id == target_id {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Cannot merge a thread into itself"
+        })));
+    }
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Database error starting transaction: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to merge threads"
+            })));
+        }
+    };
+
+    // Lock both rows in id order so two opposite merges can't deadlock
+    let threads_result = sqlx::query_as::<_, db::Thread>(
+        "SELECT * FROM threads WHERE id = ANY($1) ORDER BY id FOR UPDATE",
+    )
+    .bind(vec![source_id, target_id])
+    .fetch_all(&mut *tx)
+    .await;
+
+    let locked_threads = match threads_result {
+        Ok(threads) => threads,
+        Err(e) => {
+            eprintln!("Database error fetching threads: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch threads"
+            })));
+        }
+    };
+
+    let source = locked_threads.iter().find(|t| t.id == source_id);
+    let target = locked_threads.iter().find(|t| t.id == target_id);
+
+    let (source, target) = match (source, target) {
+        (Some(source), Some(target)) => (source, target),
+        (None, _) => {
+            return Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Source thread not found"
+            })));
+        }
+        (_, None) => {
+            return Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Thread not found"
+            })));
+        }
+    };
+
+    if source.user_id != target.user_id && !merge_data.force.unwrap_or(false) {
+        return Ok(HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Threads belong to different users",
+            "source_user_id": source.user_id,
+            "target_user_id": target.user_id
+        })));
+    }
+
+    let merged_by_id = merge_data.merged_by_id.as_deref();
+    let merged_by_tag = merge_data.merged_by_tag.as_deref();
+
+    // A message already linked to both threads only needs to survive once
+    let link_result = sqlx::query(
+        r#"
+        INSERT INTO thread_messages (thread_id, message_id)
+        SELECT $2, message_id FROM thread_messages WHERE thread_id = $1
+        ON CONFLICT (thread_id, message_id) DO NOTHING
+        "#,
+    )
+    .bind(source_id)
+    .bind(target_id)
+    .execute(&mut *tx)
+    .await;
+
+    if let Err(e) = link_result {
+        eprintln!("Database error moving thread messages: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to merge threads"
+        })));
+    }
+
+    let messages_moved = match sqlx::query("DELETE FROM thread_messages WHERE thread_id = $1")
+        .bind(source_id)
+        .execute(&mut *tx)
+        .await
+    {
+        Ok(result) => result.rows_affected(),
+        Err(e) => {
+            eprintln!("Database error moving thread messages: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to merge threads"
+            })));
+        }
+    };
+
+    let notes_moved = match sqlx::query("UPDATE notes SET thread_id = $2 WHERE thread_id = $1")
+        .bind(source_id)
+        .bind(target_id)
+        .execute(&mut *tx)
+        .await
+    {
+        Ok(result) => result.rows_affected(),
+        Err(e) => {
+            eprintln!("Database error moving notes: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to merge threads"
+            })));
+        }
+    };
+
+    if source.is_open {
+        let close_reason = format!("Merged into thread {}", target_id);
+        if let Err(e) = threads::close_locked_thread(
+            &mut tx,
+            source_id,
+            merged_by_id,
+            merged_by_tag,
+            Some(&close_reason),
+        )
+        .await
+        {
+            eprintln!("Database error closing merged thread: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to merge threads"
+            })));
+        }
+    }
+
+    // Record the merge on both sides so either history explains where the messages went
+    for (event_thread_id, metadata) in [
+        (
+            target_id,
+            serde_json::json!({ "source_thread_id": source_id, "messages_moved": messages_moved, "notes_moved": notes_moved }),
+        ),
+        (
+            source_id,
+            serde_json::json!({ "target_thread_id": target_id }),
+        ),
+    ] {
+        if let Err(e) = thread_events::record_event(
+            &mut *tx,
+            event_thread_id,
+            thread_events::EVENT_MERGE,
+            merged_by_id,
+            merged_by_tag,
+            None,
+            Some(metadata),
+        )
+        .await
+        {
+            eprintln!("Database error recording merge event: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to merge threads"
+            })));
+        }
+    }
+
+    let updated_target_result = sqlx::query_as::<_, db::Thread>(
+        "UPDATE threads SET updated_at = NOW() WHERE id = $1 RETURNING *",
+    )
+    .bind(target_id)
+    .fetch_one(&mut *tx)
+    .await;
+
+    let updated_target = match updated_target_result {
+        Ok(thread) => thread,
+        Err(e) => {
+            eprintln!("Database error updating merged thread: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to merge threads"
+            })));
+        }
+    };
+
+    if let Err(e) = tx.commit().await {
+        eprintln!("Database error committing thread merge: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to merge threads"
+        })));
+    }
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "thread": updated_target,
+        "source_thread_id": source_id,
+        "messages_moved": messages_moved,
+        "notes_moved": notes_moved
+    })))
+}
@@ -1,28 +1,100 @@
+use crate::attachments;
+use crate::auth;
 use crate::db;
-use crate::structs::CreateMessage;
-use actix_web::{get, post, web, HttpResponse, Responder, Result};
-use sqlx::PgPool;
+use crate::storage::{self, AttachmentStore};
+use crate::structs::{CreateMessage, EditMessage};
+use actix_web::{delete, get, patch, post, web, HttpRequest, HttpResponse, Responder, Result};
+use sqlx::{PgConnection, PgPool};
 use uuid::Uuid;
 
+pub const KIND_USER_INBOUND: &str = "user_inbound";
+pub const KIND_STAFF_REPLY: &str = "staff_reply";
+pub const KIND_STAFF_ANONYMOUS_REPLY: &str = "staff_anonymous_reply";
+pub const VALID_KINDS: [&str; 5] = [
+    KIND_USER_INBOUND,
+    KIND_STAFF_REPLY,
+    KIND_STAFF_ANONYMOUS_REPLY,
+    "system",
+    "macro",
+];
+
+// The kind the caller asked for, either directly or via the anonymous flag. None means infer it.
+pub fn requested_kind(message: &CreateMessage) -> Result<Option<&str>, HttpResponse> {
+    let kind = message.kind.as_deref();
+
+    if let Some(kind) = kind {
+        if !VALID_KINDS.contains(&kind) {
+            return Err(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid message kind. Must be one of: user_inbound, staff_reply, staff_anonymous_reply, system, macro"
+            })));
+        }
+    }
+
+    if !message.anonymous.unwrap_or(false) {
+        return Ok(kind);
+    }
+
+    match kind {
+        None | Some(KIND_STAFF_ANONYMOUS_REPLY) => Ok(Some(KIND_STAFF_ANONYMOUS_REPLY)),
+        Some(_) => Err(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Anonymous messages must use kind staff_anonymous_reply"
+        }))),
+    }
+}
+
+pub fn anonymous_display_name() -> String {
+    std::env::var("ANONYMOUS_STAFF_NAME")
+        .ok()
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "Staff".to_string())
+}
+
+// Anonymous replies keep their real author in the database; only admins get to see it
+pub fn hide_anonymous_authors(messages: &mut [db::Message], is_admin: bool) {
+    if is_admin {
+        return;
+    }
+
+    let display_name = anonymous_display_name();
+    for message in messages
+        .iter_mut()
+        .filter(|m| m.kind == KIND_STAFF_ANONYMOUS_REPLY)
+    {
+        message.author_id = String::new();
+        message.author_tag = display_name.clone();
+    }
+}
+
 #[get("/messages")]
-async fn get_messages(pool: web::Data<PgPool>) -> impl Responder {
+async fn get_messages(req: HttpRequest, pool: web::Data<PgPool>) -> impl Responder {
     let messages_result = sqlx::query_as::<_, db::Message>("SELECT * FROM messages")
         .fetch_all(pool.get_ref())
         .await;
 
-    match messages_result {
-        Ok(messages) => HttpResponse::Ok().json(messages),
+    let mut messages = match messages_result {
+        Ok(messages) => messages,
         Err(e) => {
             eprintln!("Database error fetching messages: {}", e);
-            HttpResponse::InternalServerError().json(serde_json::json!({
+            return HttpResponse::InternalServerError().json(serde_json::json!({
                 "error": "Failed to fetch messages"
-            }))
+            }));
         }
+    };
+
+    if let Err(e) = attachments::attach_to_messages(pool.get_ref(), &mut messages).await {
+        eprintln!("Database error fetching attachments: {}", e);
+        return HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to fetch messages"
+        }));
     }
+
+    hide_anonymous_authors(&mut messages, auth::is_admin(&req));
+    HttpResponse::Ok().json(messages)
 }
 
 #[post("/messages")]
 async fn create_message(
+    req: HttpRequest,
     pool: web::Data<PgPool>,
     message: web::Json<CreateMessage>,
 ) -> Result<impl Responder> {
@@ -33,54 +105,403 @@ async fn create_message(
         })));
     }
 
+    // Without a thread there is nothing to infer direction from, so assume a staff reply
+    let kind = match requested_kind(&message) {
+        Ok(kind) => kind.unwrap_or(KIND_STAFF_REPLY),
+        Err(response) => return Ok(response),
+    };
+
+    let attachments = message.attachments.clone().unwrap_or_default();
+    if let Err(response) = attachments::validate_attachments(&attachments) {
+        return Ok(response);
+    }
+
     let created_at = chrono::Utc::now();
     let id = Uuid::new_v4();
-    let attachments = message
-        .attachments
-        .clone()
-        .unwrap_or_else(|| serde_json::json!([]));
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Database error starting transaction: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to create message"
+            })));
+        }
+    };
 
     let new_message_result = sqlx::query_as::<_, db::Message>(
-        "INSERT INTO messages (id, author_id, author_tag, content, created_at, attachments) VALUES ($1, $2, $3, $4, $5, $6) RETURNING *",
+        "INSERT INTO messages (id, author_id, author_tag, content, created_at, discord_message_id, kind) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *",
     )
-    .bind(&id)
+    .bind(id)
     .bind(&message.author_id)
     .bind(&message.author_tag)
     .bind(&message.content)
-    .bind(&created_at)
-    .bind(&attachments)
-    .fetch_one(pool.get_ref())
+    .bind(created_at)
+    .bind(&message.discord_message_id)
+    .bind(kind)
+    .fetch_one(&mut *tx)
     .await;
 
-    match new_message_result {
-        Ok(new_message) => Ok(HttpResponse::Ok().json(new_message)),
+    let mut new_message = match new_message_result {
+        Ok(new_message) => new_message,
         Err(sqlx::Error::Database(db_err)) => {
             if let Some(constraint) = db_err.constraint() {
                 match constraint {
                     "chk_author_id_format" => {
-                        Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
                             "error": "Invalid author ID format"
-                        })))
+                        })));
+                    }
+                    "idx_messages_discord_message_id" => {
+                        return Ok(HttpResponse::Conflict().json(serde_json::json!({
+                            "error": "A message with this Discord message ID already exists"
+                        })));
                     }
                     _ => {
                         eprintln!("Database constraint violation: {}", constraint);
-                        Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
                             "error": "Data validation failed"
-                        })))
+                        })));
                     }
                 }
             } else {
                 eprintln!("Database error creating message: {}", db_err);
-                Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
                     "error": "Failed to create message"
-                })))
+                })));
             }
         }
         Err(e) => {
             eprintln!("Database error creating message: {}", e);
-            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to create message"
+            })));
+        }
+    };
+
+    match attachments::insert_attachments(&mut tx, id, &attachments).await {
+        Ok(inserted) => new_message.attachments = inserted,
+        Err(e) => {
+            eprintln!("Database error storing attachments: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
                 "error": "Failed to create message"
-            })))
+            })));
+        }
+    }
+
+    if let Err(e) = tx.commit().await {
+        eprintln!("Database error committing message: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to create message"
+        })));
+    }
+
+    hide_anonymous_authors(std::slice::from_mut(&mut new_message), auth::is_admin(&req));
+    Ok(HttpResponse::Ok().json(new_message))
+}
+
+// Messages can be addressed by their UUID or by the Discord message ID the bot knows them by
+async fn fetch_message_for_update(
+    conn: &mut PgConnection,
+    key: &str,
+) -> Result<Option<db::Message>, sqlx::Error> {
+    match Uuid::parse_str(key) {
+        Ok(id) => {
+            sqlx::query_as::<_, db::Message>("SELECT * FROM messages WHERE id = $1 FOR UPDATE")
+                .bind(id)
+                .fetch_optional(&mut *conn)
+                .await
+        }
+        Err(_) => {
+            sqlx::query_as::<_, db::Message>(
+                "SELECT * FROM messages WHERE discord_message_id = $1 FOR UPDATE",
+            )
+            .bind(key)
+            .fetch_optional(&mut *conn)
+            .await
+        }
+    }
+}
+
+// Same lookup without the row lock, for read-only endpoints
+async fn find_message(pool: &PgPool, key: &str) -> Result<Option<db::Message>, sqlx::Error> {
+    match Uuid::parse_str(key) {
+        Ok(id) => {
+            sqlx::query_as::<_, db::Message>("SELECT * FROM messages WHERE id = $1")
+                .bind(id)
+                .fetch_optional(pool)
+                .await
+        }
+        Err(_) => {
+            sqlx::query_as::<_, db::Message>("SELECT * FROM messages WHERE discord_message_id = $1")
+                .bind(key)
+                .fetch_optional(pool)
+                .await
+        }
+    }
+}
+
+#[patch("/messages/{id}")]
+async fn edit_message(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    store: Option<web::Data<dyn AttachmentStore>>,
+    key: web::Path<String>,
+    edit_data: web::Json<EditMessage>,
+) -> Result<impl Responder> {
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Database error starting transaction: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to edit message"
+            })));
         }
+    };
+
+    let mut message = match fetch_message_for_update(&mut tx, &key).await {
+        Ok(Some(message)) => message,
+        Ok(None) => {
+            return Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Message not found"
+            })));
+        }
+        Err(e) => {
+            eprintln!("Database error fetching message: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch message"
+            })));
+        }
+    };
+
+    if message.deleted_at.is_some() {
+        return Ok(HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Cannot edit a deleted message"
+        })));
     }
+
+    if let Some(attachments) = &edit_data.attachments {
+        if let Err(response) = attachments::validate_attachments(attachments) {
+            return Ok(response);
+        }
+    }
+
+    if let Err(e) =
+        attachments::attach_to_messages(&mut *tx, std::slice::from_mut(&mut message)).await
+    {
+        eprintln!("Database error fetching attachments: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to fetch message"
+        })));
+    }
+
+    let attachments_changed = edit_data
+        .attachments
+        .as_ref()
+        .is_some_and(|attachments| !attachments::same_attachments(&message.attachments, attachments));
+
+    // Discord fires update events for embed resolution too; only real changes become revisions
+    if message.content == edit_data.content && !attachments_changed {
+        hide_anonymous_authors(std::slice::from_mut(&mut message), auth::is_admin(&req));
+        return Ok(HttpResponse::Ok().json(message));
+    }
+
+    let revision_result = sqlx::query(
+        "INSERT INTO message_revisions (message_id, content, attachments, edited_by_id) VALUES ($1, $2, $3, $4)",
+    )
+    .bind(message.id)
+    .bind(&message.content)
+    .bind(sqlx::types::Json(attachments::as_submitted(&message.attachments)))
+    .bind(&edit_data.edited_by_id)
+    .execute(&mut *tx)
+    .await;
+
+    if let Err(e) = revision_result {
+        eprintln!("Database error recording message revision: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to edit message"
+        })));
+    }
+
+    let updated_message_result = sqlx::query_as::<_, db::Message>(
+        "UPDATE messages SET content = $2, edited_at = NOW() WHERE id = $1 RETURNING *",
+    )
+    .bind(message.id)
+    .bind(&edit_data.content)
+    .fetch_one(&mut *tx)
+    .await;
+
+    let mut updated_message = match updated_message_result {
+        Ok(message) => message,
+        Err(e) => {
+            eprintln!("Database error editing message: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to edit message"
+            })));
+        }
+    };
+
+    let mut stale_storage_keys = Vec::new();
+    match (&edit_data.attachments, attachments_changed) {
+        (Some(new_attachments), true) => {
+            match attachments::replace_attachments(&mut tx, message.id, new_attachments).await {
+                Ok((inserted, storage_keys)) => {
+                    updated_message.attachments = inserted;
+                    stale_storage_keys = storage_keys;
+                }
+                Err(e) => {
+                    eprintln!("Database error storing attachments: {}", e);
+                    return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                        "error": "Failed to edit message"
+                    })));
+                }
+            }
+        }
+        _ => updated_message.attachments = message.attachments,
+    }
+
+    if let Err(e) = tx.commit().await {
+        eprintln!("Database error committing message edit: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to edit message"
+        })));
+    }
+
+    if let Some(store) = &store {
+        storage::delete_keys(store.get_ref(), &stale_storage_keys).await;
+    }
+
+    hide_anonymous_authors(
+        std::slice::from_mut(&mut updated_message),
+        auth::is_admin(&req),
+    );
+    Ok(HttpResponse::Ok().json(updated_message))
+}
+
+#[delete("/messages/{id}")]
+async fn delete_message(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    key: web::Path<String>,
+) -> Result<impl Responder> {
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Database error starting transaction: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to delete message"
+            })));
+        }
+    };
+
+    let mut message = match fetch_message_for_update(&mut tx, &key).await {
+        Ok(Some(message)) => message,
+        Ok(None) => {
+            return Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Message not found"
+            })));
+        }
+        Err(e) => {
+            eprintln!("Database error fetching message: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch message"
+            })));
+        }
+    };
+
+    if let Err(e) =
+        attachments::attach_to_messages(&mut *tx, std::slice::from_mut(&mut message)).await
+    {
+        eprintln!("Database error fetching attachments: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to fetch message"
+        })));
+    }
+
+    // Soft delete: the content stays so staff can still see what was said
+    if message.deleted_at.is_some() {
+        hide_anonymous_authors(std::slice::from_mut(&mut message), auth::is_admin(&req));
+        return Ok(HttpResponse::Ok().json(message));
+    }
+
+    let deleted_message_result = sqlx::query_as::<_, db::Message>(
+        "UPDATE messages SET deleted_at = NOW() WHERE id = $1 RETURNING *",
+    )
+    .bind(message.id)
+    .fetch_one(&mut *tx)
+    .await;
+
+    let mut deleted_message = match deleted_message_result {
+        Ok(deleted_message) => db::Message {
+            attachments: message.attachments,
+            ..deleted_message
+        },
+        Err(e) => {
+            eprintln!("Database error deleting message: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to delete message"
+            })));
+        }
+    };
+
+    if let Err(e) = tx.commit().await {
+        eprintln!("Database error committing message deletion: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to delete message"
+        })));
+    }
+
+    hide_anonymous_authors(
+        std::slice::from_mut(&mut deleted_message),
+        auth::is_admin(&req),
+    );
+    Ok(HttpResponse::Ok().json(deleted_message))
+}
+
+#[get("/messages/{id}/revisions")]
+async fn get_message_revisions(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    key: web::Path<String>,
+) -> impl Responder {
+    let message = match find_message(pool.get_ref(), &key).await {
+        Ok(Some(message)) => message,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Message not found"
+            }));
+        }
+        Err(e) => {
+            eprintln!("Database error fetching message: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch message revisions"
+            }));
+        }
+    };
+
+    let revisions_result = sqlx::query_as::<_, db::MessageRevision>(
+        "SELECT * FROM message_revisions WHERE message_id = $1 ORDER BY created_at ASC, id ASC",
+    )
+    .bind(message.id)
+    .fetch_all(pool.get_ref())
+    .await;
+
+    let mut revisions = match revisions_result {
+        Ok(revisions) => revisions,
+        Err(e) => {
+            eprintln!("Database error fetching message revisions: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch message revisions"
+            }));
+        }
+    };
+
+    // The editor of an anonymous reply is its author, so hide them the same way
+    if message.kind == KIND_STAFF_ANONYMOUS_REPLY && !auth::is_admin(&req) {
+        for revision in &mut revisions {
+            revision.edited_by_id = None;
+        }
+    }
+
+    HttpResponse::Ok().json(revisions)
 }
@@ -1,15 +1,37 @@
+use crate::auth;
 use crate::db;
-use crate::structs::CreateNote;
-use actix_web::{get, post, web, HttpResponse, Responder, Result};
-use sqlx::PgPool;
+use crate::structs::{CreateNote, EditNote};
+use actix_web::{delete, get, patch, post, web, HttpRequest, HttpResponse, Responder, Result};
+use serde::Deserialize;
+use sqlx::{PgConnection, PgPool};
 use uuid::Uuid;
 
+#[derive(Deserialize)]
+pub struct NotesQuery {
+    pub include_deleted: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct DeleteNoteQuery {
+    pub deleted_by_id: Option<String>,
+}
+
 #[get("/threads/{id}/notes")]
-async fn get_thread_notes(pool: web::Data<PgPool>, thread_id: web::Path<i32>) -> impl Responder {
+async fn get_thread_notes(
+    pool: web::Data<PgPool>,
+    thread_id: web::Path<i32>,
+    query: web::Query<NotesQuery>,
+) -> impl Responder {
+    // Pinned notes carry key context, so they always come first
     let notes_result = sqlx::query_as::<_, db::Note>(
-        "SELECT * FROM notes WHERE thread_id = $1 ORDER BY created_at ASC",
+        r#"
+        SELECT * FROM notes
+        WHERE thread_id = $1 AND ($2 OR deleted_at IS NULL)
+        ORDER BY pinned DESC, created_at ASC
+        "#,
     )
     .bind(thread_id.into_inner())
+    .bind(query.include_deleted.unwrap_or(false))
     .fetch_all(pool.get_ref())
     .await;
 
@@ -41,28 +63,19 @@ async fn add_note_to_thread(
     let note_id = Uuid::new_v4();
     let created_at = chrono::Utc::now();
 
-    let insert_result = sqlx::query("INSERT INTO notes (id, thread_id, author_id, author_tag, content, created_at) VALUES ($1, $2, $3, $4, $5, $6)")
-        .bind(&note_id)
+    let insert_result = sqlx::query_as::<_, db::Note>("INSERT INTO notes (id, thread_id, author_id, author_tag, content, created_at, pinned) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *")
+        .bind(note_id)
         .bind(thread_id)
         .bind(&note.author_id)
         .bind(&note.author_tag)
         .bind(&note.content)
-        .bind(&created_at)
-        .execute(pool.get_ref())
+        .bind(created_at)
+        .bind(note.pinned.unwrap_or(false))
+        .fetch_one(pool.get_ref())
         .await;
 
     match insert_result {
-        Ok(_) => {
-            let new_note = db::Note {
-                id: note_id,
-                thread_id,
-                author_id: note.author_id.clone(),
-                author_tag: note.author_tag.clone(),
-                content: note.content.clone(),
-                created_at,
-            };
-            Ok(HttpResponse::Ok().json(new_note))
-        }
+        Ok(new_note) => Ok(HttpResponse::Ok().json(new_note)),
         Err(sqlx::Error::Database(db_err)) => {
             if let Some(constraint) = db_err.constraint() {
                 match constraint {
@@ -96,3 +109,245 @@ async fn add_note_to_thread(
         }
     }
 }
+
+async fn fetch_note_for_update(
+    conn: &mut PgConnection,
+    thread_id: i32,
+    note_id: Uuid,
+) -> Result<Option<db::Note>, sqlx::Error> {
+    sqlx::query_as::<_, db::Note>("SELECT * FROM notes WHERE id = $1 AND thread_id = $2 FOR UPDATE")
+        .bind(note_id)
+        .bind(thread_id)
+        .fetch_optional(&mut *conn)
+        .await
+}
+
+// Notes belong to whoever wrote them; admins can change anyone's. The actor ID is whatever the
+// caller sends, so the author check only keeps honest clients (the bot) in line; the admin
+// token is the only thing actually enforced.
+fn can_modify_note(req: &HttpRequest, note: &db::Note, actor_id: Option<&str>) -> bool {
+    actor_id == Some(note.author_id.as_str()) || auth::is_admin(req)
+}
+
+#[patch("/threads/{id}/notes/{note_id}")]
+async fn edit_note(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    path: web::Path<(i32, Uuid)>,
+    edit_data: web::Json<EditNote>,
+) -> Result<impl Responder> {
+    let (thread_id, note_id) = path.into_inner();
+
+    if edit_data
+        .content
+        .as_ref()
+        .is_some_and(|content| content.trim().is_empty())
+    {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Note content cannot be empty"
+        })));
+    }
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Database error starting transaction: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to edit note"
+            })));
+        }
+    };
+
+    let note = match fetch_note_for_update(&mut tx, thread_id, note_id).await {
+        Ok(Some(note)) => note,
+        Ok(None) => {
+            return Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Note not found"
+            })));
+        }
+        Err(e) => {
+            eprintln!("Database error fetching note: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch note"
+            })));
+        }
+    };
+
+    if !can_modify_note(&req, &note, Some(&edit_data.edited_by_id)) {
+        return Ok(HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Only the note's author or an admin can edit it"
+        })));
+    }
+
+    if note.deleted_at.is_some() {
+        return Ok(HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Cannot edit a deleted note"
+        })));
+    }
+
+    let content = edit_data.content.as_deref().unwrap_or(&note.content);
+    let pinned = edit_data.pinned.unwrap_or(note.pinned);
+    let content_changed = content != note.content;
+
+    if !content_changed && pinned == note.pinned {
+        return Ok(HttpResponse::Ok().json(note));
+    }
+
+    if content_changed {
+        let revision_result = sqlx::query(
+            "INSERT INTO note_revisions (note_id, content, edited_by_id) VALUES ($1, $2, $3)",
+        )
+        .bind(note.id)
+        .bind(&note.content)
+        .bind(&edit_data.edited_by_id)
+        .execute(&mut *tx)
+        .await;
+
+        if let Err(e) = revision_result {
+            eprintln!("Database error recording note revision: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to edit note"
+            })));
+        }
+    }
+
+    // Pinning alone isn't an edit of what the note says, so it leaves edited_at alone
+    let updated_note_result = sqlx::query_as::<_, db::Note>(
+        r#"
+        UPDATE notes SET
+            content = $2,
+            pinned = $3,
+            edited_at = CASE WHEN $4 THEN NOW() ELSE edited_at END
+        WHERE id = $1
+        RETURNING *
+        "#,
+    )
+    .bind(note.id)
+    .bind(content)
+    .bind(pinned)
+    .bind(content_changed)
+    .fetch_one(&mut *tx)
+    .await;
+
+    let updated_note = match updated_note_result {
+        Ok(note) => note,
+        Err(e) => {
+            eprintln!("Database error editing note: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to edit note"
+            })));
+        }
+    };
+
+    if let Err(e) = tx.commit().await {
+        eprintln!("Database error committing note edit: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to edit note"
+        })));
+    }
+
+    Ok(HttpResponse::Ok().json(updated_note))
+}
+
+#[delete("/threads/{id}/notes/{note_id}")]
+async fn delete_note(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    path: web::Path<(i32, Uuid)>,
+    query: web::Query<DeleteNoteQuery>,
+) -> Result<impl Responder> {
+    let (thread_id, note_id) = path.into_inner();
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Database error starting transaction: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to delete note"
+            })));
+        }
+    };
+
+    let note = match fetch_note_for_update(&mut tx, thread_id, note_id).await {
+        Ok(Some(note)) => note,
+        Ok(None) => {
+            return Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Note not found"
+            })));
+        }
+        Err(e) => {
+            eprintln!("Database error fetching note: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch note"
+            })));
+        }
+    };
+
+    if !can_modify_note(&req, &note, query.deleted_by_id.as_deref()) {
+        return Ok(HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Only the note's author or an admin can delete it"
+        })));
+    }
+
+    // Soft delete: the note drops out of listings but stays recoverable
+    if note.deleted_at.is_some() {
+        return Ok(HttpResponse::Ok().json(note));
+    }
+
+    let deleted_note_result = sqlx::query_as::<_, db::Note>(
+        "UPDATE notes SET deleted_at = NOW(), pinned = FALSE WHERE id = $1 RETURNING *",
+    )
+    .bind(note.id)
+    .fetch_one(&mut *tx)
+    .await;
+
+    let deleted_note = match deleted_note_result {
+        Ok(note) => note,
+        Err(e) => {
+            eprintln!("Database error deleting note: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to delete note"
+            })));
+        }
+    };
+
+    if let Err(e) = tx.commit().await {
+        eprintln!("Database error committing note deletion: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to delete note"
+        })));
+    }
+
+    Ok(HttpResponse::Ok().json(deleted_note))
+}
+
+#[get("/threads/{id}/notes/{note_id}/revisions")]
+async fn get_note_revisions(
+    pool: web::Data<PgPool>,
+    path: web::Path<(i32, Uuid)>,
+) -> impl Responder {
+    let (thread_id, note_id) = path.into_inner();
+
+    let revisions_result = sqlx::query_as::<_, db::NoteRevision>(
+        r#"
+        SELECT r.* FROM note_revisions r
+        JOIN notes n ON n.id = r.note_id
+        WHERE r.note_id = $1 AND n.thread_id = $2
+        ORDER BY r.created_at ASC, r.id ASC
+        "#,
+    )
+    .bind(note_id)
+    .bind(thread_id)
+    .fetch_all(pool.get_ref())
+    .await;
+
+    match revisions_result {
+        Ok(revisions) => HttpResponse::Ok().json(revisions),
+        Err(e) => {
+            eprintln!("Database error fetching note revisions: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch note revisions"
+            }))
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, TimeZone, Utc};
+use uuid::Uuid;
+
+// Cursors are opaque to clients: a base64 wrapper around the keyset position.
+// Threads are keyed on `id`, messages on `(created_at, id)` with microsecond
+// precision to match Postgres timestamptz.
+
+pub fn encode_thread_cursor(id: i32) -> String {
+    URL_SAFE_NO_PAD.encode(format!("t:{}", id))
+}
+
+pub fn decode_thread_cursor(cursor: &str) -> Option<i32> {
+    let decoded = String::from_utf8(URL_SAFE_NO_PAD.decode(cursor).ok()?).ok()?;
+    decoded.strip_prefix("t:")?.parse().ok()
+}
+
+pub fn encode_message_cursor(created_at: DateTime<Utc>, id: Uuid) -> String {
+    URL_SAFE_NO_PAD.encode(format!("m:{}:{}", created_at.timestamp_micros(), id))
+}
+
+pub fn decode_message_cursor(cursor: &str) -> Option<(DateTime<Utc>, Uuid)> {
+    let decoded = String::from_utf8(URL_SAFE_NO_PAD.decode(cursor).ok()?).ok()?;
+    let (micros, id) = decoded.strip_prefix("m:")?.split_once(':')?;
+    let created_at = Utc.timestamp_micros(micros.parse().ok()?).single()?;
+    Some((created_at, Uuid::parse_str(id).ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thread_cursor_round_trips() {
+        for id in [1, 42, i32::MAX, 0, -7] {
+            assert_eq!(decode_thread_cursor(&encode_thread_cursor(id)), Some(id));
+        }
+    }
+
+    #[test]
+    fn message_cursor_round_trips_with_microseconds() {
+        let created_at = Utc.timestamp_micros(1_700_000_000_123_456).unwrap();
+        let id = Uuid::new_v4();
+        assert_eq!(
+            decode_message_cursor(&encode_message_cursor(created_at, id)),
+            Some((created_at, id))
+        );
+    }
+
+    #[test]
+    fn cursors_are_url_safe() {
+        let cursor = encode_message_cursor(Utc::now(), Uuid::new_v4());
+        assert!(cursor
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+    }
+
+    #[test]
+    fn malformed_cursors_are_rejected() {
+        for cursor in ["", "!!!", "not a cursor", "dDox=", "dDox\n"] {
+            assert_eq!(decode_thread_cursor(cursor), None, "{:?}", cursor);
+            assert_eq!(decode_message_cursor(cursor), None, "{:?}", cursor);
+        }
+        // Valid base64 that isn't UTF-8
+        let binary = URL_SAFE_NO_PAD.encode([0xff, 0xfe, 0xfd]);
+        assert_eq!(decode_thread_cursor(&binary), None);
+        assert_eq!(decode_message_cursor(&binary), None);
+    }
+
+    #[test]
+    fn tampered_thread_cursors_are_rejected() {
+        for payload in ["t:", "t:abc", "t:1.5", "t:99999999999", "x:1", "1", "t:1:2"] {
+            let cursor = URL_SAFE_NO_PAD.encode(payload);
+            assert_eq!(decode_thread_cursor(&cursor), None, "{:?}", payload);
+        }
+    }
+
+    #[test]
+    fn tampered_message_cursors_are_rejected() {
+        let id = Uuid::new_v4();
+        for payload in [
+            "m:".to_string(),
+            format!("m:{}", 1_700_000_000_000_000i64),
+            format!("m:abc:{}", id),
+            format!("m:{}:not-a-uuid", 1_700_000_000_000_000i64),
+            format!("m:{}:{}", i64::MAX, id),
+            format!("t:{}:{}", 1_700_000_000_000_000i64, id),
+        ] {
+            let cursor = URL_SAFE_NO_PAD.encode(&payload);
+            assert_eq!(decode_message_cursor(&cursor), None, "{:?}", payload);
+        }
+    }
+
+    #[test]
+    fn cursors_of_the_other_kind_are_rejected() {
+        let message_cursor = encode_message_cursor(Utc::now(), Uuid::new_v4());
+        assert_eq!(decode_thread_cursor(&message_cursor), None);
+        assert_eq!(decode_message_cursor(&encode_thread_cursor(1)), None);
+    }
+}
@@ -0,0 +1,192 @@
+use crate::auth;
+use crate::storage::{self, AttachmentStore};
+use actix_web::{delete, web, HttpRequest, HttpResponse, Responder, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+#[derive(Deserialize)]
+struct PurgeQuery {
+    dry_run: Option<bool>,
+}
+
+#[derive(Serialize, Default)]
+pub struct PurgeReport {
+    pub messages: u64,
+    pub attachments: u64,
+    pub thread_messages: u64,
+    pub notes: u64,
+    pub events: u64,
+    pub assignments: u64,
+    pub tags: u64,
+    pub scheduled_closes: u64,
+    // Archived files to remove from storage once the transaction has committed
+    #[serde(skip)]
+    pub storage_keys: Vec<String>,
+}
+
+// Removes everything hanging off a thread, then the thread itself. Runs inside the caller's
+// transaction so a dry run can report real counts and roll back.
+pub async fn purge_thread_rows(
+    conn: &mut PgConnection,
+    thread_id: i32,
+) -> Result<PurgeReport, sqlx::Error> {
+    let mut report = PurgeReport::default();
+
+    let message_ids: Vec<Uuid> =
+        sqlx::query_scalar("DELETE FROM thread_messages WHERE thread_id = $1 RETURNING message_id")
+            .bind(thread_id)
+            .fetch_all(&mut *conn)
+            .await?;
+    report.thread_messages = message_ids.len() as u64;
+
+    // Attachments go with their message, so they follow the same rule
+    let storage_keys: Vec<Option<String>> = sqlx::query_scalar(
+        r#"
+        DELETE FROM attachments a
+        WHERE a.message_id = ANY($1)
+        AND NOT EXISTS (SELECT 1 FROM thread_messages tm WHERE tm.message_id = a.message_id)
+        RETURNING a.storage_key
+        "#,
+    )
+    .bind(&message_ids)
+    .fetch_all(&mut *conn)
+    .await?;
+    report.attachments = storage_keys.len() as u64;
+    report.storage_keys = storage_keys.into_iter().flatten().collect();
+
+    // Only drop messages no other thread still links to
+    report.messages = sqlx::query(
+        r#"
+        DELETE FROM messages m
+        WHERE m.id = ANY($1)
+        AND NOT EXISTS (SELECT 1 FROM thread_messages tm WHERE tm.message_id = m.id)
+        "#,
+    )
+    .bind(&message_ids)
+    .execute(&mut *conn)
+    .await?
+    .rows_affected();
+
+    report.notes = sqlx::query("DELETE FROM notes WHERE thread_id = $1")
+        .bind(thread_id)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+
+    report.events = sqlx::query("DELETE FROM thread_events WHERE thread_id = $1")
+        .bind(thread_id)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+
+    report.assignments = sqlx::query("DELETE FROM thread_assignments WHERE thread_id = $1")
+        .bind(thread_id)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+
+    report.tags = sqlx::query("DELETE FROM thread_tags WHERE thread_id = $1")
+        .bind(thread_id)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+
+    report.scheduled_closes = sqlx::query("DELETE FROM scheduled_closes WHERE thread_id = $1")
+        .bind(thread_id)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+
+    sqlx::query("DELETE FROM threads WHERE id = $1")
+        .bind(thread_id)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(report)
+}
+
+#[delete("/threads/{id}")]
+async fn delete_thread(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    store: Option<web::Data<dyn AttachmentStore>>,
+    thread_id: web::Path<i32>,
+    query: web::Query<PurgeQuery>,
+) -> Result<impl Responder> {
+    if !auth::is_admin(&req) {
+        return Ok(HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Admin access required"
+        })));
+    }
+
+    let thread_id = thread_id.into_inner();
+    let dry_run = query.dry_run.unwrap_or(false);
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Database error starting transaction: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to delete thread"
+            })));
+        }
+    };
+
+    let thread_exists_result: Result<Option<i32>, sqlx::Error> =
+        sqlx::query_scalar("SELECT id FROM threads WHERE id = $1 FOR UPDATE")
+            .bind(thread_id)
+            .fetch_optional(&mut *tx)
+            .await;
+
+    match thread_exists_result {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Thread not found"
+            })));
+        }
+        Err(e) => {
+            eprintln!("Database error fetching thread: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch thread"
+            })));
+        }
+    }
+
+    let report = match purge_thread_rows(&mut tx, thread_id).await {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Database error deleting thread {}: {}", thread_id, e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to delete thread"
+            })));
+        }
+    };
+
+    let finish_result = if dry_run {
+        tx.rollback().await
+    } else {
+        tx.commit().await
+    };
+
+    if let Err(e) = finish_result {
+        eprintln!("Database error finishing thread deletion: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to delete thread"
+        })));
+    }
+
+    if !dry_run {
+        if let Some(store) = &store {
+            storage::delete_keys(store.get_ref(), &report.storage_keys).await;
+        }
+        println!("Thread {} purged by admin", thread_id);
+    }
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "thread_id": thread_id,
+        "dry_run": dry_run,
+        "deleted": report
+    })))
+}
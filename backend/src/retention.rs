@@ -0,0 +1,379 @@
+use crate::auth;
+use crate::purge;
+use crate::storage::{self, AttachmentStore};
+use actix_web::{get, web, HttpRequest, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool};
+use std::env;
+
+const PRUNED_CONTENT: &str = "[removed by retention policy]";
+
+// Caps each run so the first pass over years of history doesn't hold connections for ages
+const THREADS_PER_RUN: i64 = 200;
+
+// Messages of the thread that no open thread still links to
+const PRUNABLE_MESSAGES: &str = r#"
+    SELECT tm.message_id FROM thread_messages tm
+    WHERE tm.thread_id = $1
+    AND NOT EXISTS (
+        SELECT 1 FROM thread_messages other
+        JOIN threads t ON t.id = other.thread_id
+        WHERE other.message_id = tm.message_id AND t.is_open = TRUE
+    )
+"#;
+
+// Messages created through POST /messages that never made it into a thread
+const ORPHAN_MESSAGES: &str = r#"
+    SELECT m.id FROM messages m
+    WHERE m.created_at < NOW() - make_interval(days => $1)
+    AND NOT EXISTS (SELECT 1 FROM thread_messages tm WHERE tm.message_id = m.id)
+"#;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum RetentionMode {
+    // Keep threads, messages and timestamps but replace their content
+    Anonymize,
+    // Remove expired threads entirely, like DELETE /threads/{id}
+    Delete,
+}
+
+impl RetentionMode {
+    fn as_str(&self) -> &'static str {
+        match self {
+            RetentionMode::Anonymize => "anonymize",
+            RetentionMode::Delete => "delete",
+        }
+    }
+}
+
+pub struct RetentionConfig {
+    pub closed_thread_days: Option<i32>,
+    pub orphan_message_days: Option<i32>,
+    pub mode: RetentionMode,
+    pub interval_secs: u64,
+}
+
+fn positive_days(name: &str) -> Option<i32> {
+    env::var(name)
+        .ok()?
+        .parse::<i32>()
+        .ok()
+        .filter(|days| *days > 0)
+}
+
+impl RetentionConfig {
+    // Retention is off unless RETENTION_CLOSED_THREAD_DAYS or RETENTION_ORPHAN_MESSAGE_DAYS is set
+    pub fn from_env() -> Option<Self> {
+        let closed_thread_days = positive_days("RETENTION_CLOSED_THREAD_DAYS");
+        let orphan_message_days = positive_days("RETENTION_ORPHAN_MESSAGE_DAYS");
+        if closed_thread_days.is_none() && orphan_message_days.is_none() {
+            return None;
+        }
+
+        // Anything but an explicit "delete" keeps the rows, so a typo never destroys data
+        let mode = match env::var("RETENTION_MODE").as_deref() {
+            Ok("delete") => RetentionMode::Delete,
+            Ok("anonymize") | Err(_) => RetentionMode::Anonymize,
+            Ok(other) => {
+                eprintln!(
+                    "Unknown RETENTION_MODE {:?}, falling back to anonymize",
+                    other
+                );
+                RetentionMode::Anonymize
+            }
+        };
+
+        let interval_secs = env::var("RETENTION_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|secs| *secs > 0)
+            .unwrap_or(3600); // Check every hour
+
+        Some(Self {
+            closed_thread_days,
+            orphan_message_days,
+            mode,
+            interval_secs,
+        })
+    }
+}
+
+#[derive(Serialize, Default)]
+struct RetentionReport {
+    threads: u64,
+    messages: u64,
+    attachments: u64,
+    notes: u64,
+    orphan_messages: u64,
+    #[serde(skip)]
+    storage_keys: Vec<String>,
+}
+
+impl RetentionReport {
+    fn add(&mut self, other: RetentionReport) {
+        self.threads += other.threads;
+        self.messages += other.messages;
+        self.attachments += other.attachments;
+        self.notes += other.notes;
+        self.orphan_messages += other.orphan_messages;
+        self.storage_keys.extend(other.storage_keys);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.threads == 0
+            && self.messages == 0
+            && self.attachments == 0
+            && self.notes == 0
+            && self.orphan_messages == 0
+    }
+}
+
+#[derive(sqlx::FromRow, Serialize)]
+struct RetentionRun {
+    id: i32,
+    mode: String,
+    counts: serde_json::Value,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    created_at: chrono::DateTime<chrono::Utc>,
+}
+
+async fn scrub_thread_content(
+    conn: &mut PgConnection,
+    thread_id: i32,
+) -> Result<RetentionReport, sqlx::Error> {
+    let mut report = RetentionReport {
+        threads: 1,
+        ..Default::default()
+    };
+
+    let storage_keys: Vec<Option<String>> = sqlx::query_scalar(&format!(
+        "DELETE FROM attachments WHERE message_id IN ({}) RETURNING storage_key",
+        PRUNABLE_MESSAGES
+    ))
+    .bind(thread_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    report.attachments = storage_keys.len() as u64;
+    report.storage_keys = storage_keys.into_iter().flatten().collect();
+
+    sqlx::query(&format!(
+        "UPDATE message_revisions SET content = $2, attachments = '[]'::jsonb WHERE message_id IN ({})",
+        PRUNABLE_MESSAGES
+    ))
+    .bind(thread_id)
+    .bind(PRUNED_CONTENT)
+    .execute(&mut *conn)
+    .await?;
+
+    report.messages = sqlx::query(&format!(
+        "UPDATE messages SET content = $2 WHERE id IN ({})",
+        PRUNABLE_MESSAGES
+    ))
+    .bind(thread_id)
+    .bind(PRUNED_CONTENT)
+    .execute(&mut *conn)
+    .await?
+    .rows_affected();
+
+    sqlx::query(
+        "UPDATE note_revisions SET content = $2 WHERE note_id IN (SELECT id FROM notes WHERE thread_id = $1)",
+    )
+    .bind(thread_id)
+    .bind(PRUNED_CONTENT)
+    .execute(&mut *conn)
+    .await?;
+
+    report.notes = sqlx::query("UPDATE notes SET content = $2 WHERE thread_id = $1")
+        .bind(thread_id)
+        .bind(PRUNED_CONTENT)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+
+    sqlx::query("UPDATE threads SET content_pruned_at = NOW() WHERE id = $1")
+        .bind(thread_id)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(report)
+}
+
+async fn prune_thread(
+    pool: &PgPool,
+    thread_id: i32,
+    mode: RetentionMode,
+) -> Result<RetentionReport, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    // Someone may have reopened the thread since the candidate query ran
+    let still_closed: Option<i32> =
+        sqlx::query_scalar("SELECT id FROM threads WHERE id = $1 AND is_open = FALSE FOR UPDATE")
+            .bind(thread_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+    if still_closed.is_none() {
+        return Ok(RetentionReport::default());
+    }
+
+    let report = match mode {
+        RetentionMode::Anonymize => scrub_thread_content(&mut tx, thread_id).await?,
+        RetentionMode::Delete => {
+            let purged = purge::purge_thread_rows(&mut tx, thread_id).await?;
+            RetentionReport {
+                threads: 1,
+                messages: purged.messages,
+                attachments: purged.attachments,
+                notes: purged.notes,
+                orphan_messages: 0,
+                storage_keys: purged.storage_keys,
+            }
+        }
+    };
+
+    tx.commit().await?;
+    Ok(report)
+}
+
+async fn prune_orphan_messages(pool: &PgPool, days: i32) -> Result<RetentionReport, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let storage_keys: Vec<Option<String>> = sqlx::query_scalar(&format!(
+        "DELETE FROM attachments WHERE message_id IN ({}) RETURNING storage_key",
+        ORPHAN_MESSAGES
+    ))
+    .bind(days)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    // Revisions go with their message through ON DELETE CASCADE
+    let orphan_messages = sqlx::query(&format!(
+        "DELETE FROM messages WHERE id IN ({})",
+        ORPHAN_MESSAGES
+    ))
+    .bind(days)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    tx.commit().await?;
+
+    Ok(RetentionReport {
+        attachments: storage_keys.len() as u64,
+        orphan_messages,
+        storage_keys: storage_keys.into_iter().flatten().collect(),
+        ..Default::default()
+    })
+}
+
+// Function to enforce the retention policy in background
+pub async fn run_retention(
+    pool: &PgPool,
+    store: Option<&dyn AttachmentStore>,
+    config: &RetentionConfig,
+) {
+    let mut report = RetentionReport::default();
+
+    if let Some(days) = config.closed_thread_days {
+        let candidates_result: Result<Vec<i32>, sqlx::Error> = sqlx::query_scalar(
+            r#"
+            SELECT id FROM threads
+            WHERE is_open = FALSE
+            AND closed_at < NOW() - make_interval(days => $1)
+            AND ($3 OR content_pruned_at IS NULL OR content_pruned_at < closed_at)
+            ORDER BY closed_at ASC
+            LIMIT $2
+            "#,
+        )
+        .bind(days)
+        .bind(THREADS_PER_RUN)
+        .bind(config.mode == RetentionMode::Delete)
+        .fetch_all(pool)
+        .await;
+
+        match candidates_result {
+            Ok(candidates) => {
+                for thread_id in candidates {
+                    match prune_thread(pool, thread_id, config.mode).await {
+                        Ok(thread_report) => report.add(thread_report),
+                        Err(e) => {
+                            eprintln!("Background retention of thread {} failed: {}", thread_id, e)
+                        }
+                    }
+                }
+            }
+            Err(e) => eprintln!("Background retention query failed: {}", e),
+        }
+    }
+
+    if let Some(days) = config.orphan_message_days {
+        match prune_orphan_messages(pool, days).await {
+            Ok(orphan_report) => report.add(orphan_report),
+            Err(e) => eprintln!("Background pruning of orphan messages failed: {}", e),
+        }
+    }
+
+    if report.is_empty() {
+        return;
+    }
+
+    if let Some(store) = store {
+        storage::delete_keys(store, &report.storage_keys).await;
+    }
+
+    let run_result = sqlx::query("INSERT INTO retention_runs (mode, counts) VALUES ($1, $2)")
+        .bind(config.mode.as_str())
+        .bind(sqlx::types::Json(&report))
+        .execute(pool)
+        .await;
+
+    if let Err(e) = run_result {
+        eprintln!("Database error recording retention run: {}", e);
+    }
+
+    println!(
+        "Background retention ({}) pruned {} threads, {} messages, {} attachments, {} notes and {} orphan messages",
+        config.mode.as_str(),
+        report.threads,
+        report.messages,
+        report.attachments,
+        report.notes,
+        report.orphan_messages
+    );
+}
+
+#[derive(Deserialize)]
+struct RetentionRunsQuery {
+    limit: Option<i64>,
+}
+
+#[get("/retention/runs")]
+async fn get_retention_runs(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    query: web::Query<RetentionRunsQuery>,
+) -> impl Responder {
+    if !auth::is_admin(&req) {
+        return HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Admin access required"
+        }));
+    }
+
+    let limit = query.limit.unwrap_or(50).clamp(1, 500);
+
+    let runs_result = sqlx::query_as::<_, RetentionRun>(
+        "SELECT * FROM retention_runs ORDER BY created_at DESC, id DESC LIMIT $1",
+    )
+    .bind(limit)
+    .fetch_all(pool.get_ref())
+    .await;
+
+    match runs_result {
+        Ok(runs) => HttpResponse::Ok().json(runs),
+        Err(e) => {
+            eprintln!("Database error fetching retention runs: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch retention runs"
+            }))
+        }
+    }
+}
@@ -0,0 +1,404 @@
+use crate::auto_close::{SYSTEM_ACTOR_ID, SYSTEM_ACTOR_TAG};
+use crate::db;
+use crate::structs::{CancelScheduledClose, CloseThread};
+use crate::thread_events;
+use crate::threads;
+use actix_web::{delete, web, HttpResponse, Responder, Result};
+use sqlx::{PgConnection, PgPool};
+use std::env;
+
+const MAX_SCHEDULE_SECS: i64 = 90 * 24 * 3600; // 90 days
+
+// How often the background task looks for due closes
+pub fn interval_secs() -> u64 {
+    env::var("SCHEDULED_CLOSE_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|secs| *secs > 0)
+        .unwrap_or(60) // Check every minute
+}
+
+// Parses durations like "90s", "30m", "24h" or "7d"
+fn parse_duration(value: &str) -> Option<chrono::Duration> {
+    let value = value.trim();
+    let unit = value.chars().last()?;
+    let amount: i64 = value[..value.len() - unit.len_utf8()].parse().ok()?;
+
+    let unit_secs = match unit {
+        's' => 1,
+        'm' => 60,
+        'h' => 3600,
+        'd' => 86400,
+        _ => return None,
+    };
+
+    let secs = amount.checked_mul(unit_secs)?;
+    if secs <= 0 || secs > MAX_SCHEDULE_SECS {
+        return None;
+    }
+
+    Some(chrono::Duration::seconds(secs))
+}
+
+pub async fn schedule_close(
+    pool: &PgPool,
+    thread_id: i32,
+    after: &str,
+    close_data: Option<&CloseThread>,
+) -> HttpResponse {
+    let delay = match parse_duration(after) {
+        Some(delay) => delay,
+        None => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid duration. Use a number followed by s, m, h or d (e.g. 24h), up to 90 days"
+            }));
+        }
+    };
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Database error starting transaction: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to schedule thread close"
+            }));
+        }
+    };
+
+    let thread_result =
+        sqlx::query_as::<_, db::Thread>("SELECT * FROM threads WHERE id = $1 FOR UPDATE")
+            .bind(thread_id)
+            .fetch_one(&mut *tx)
+            .await;
+
+    let thread = match thread_result {
+        Ok(thread) => thread,
+        Err(sqlx::Error::RowNotFound) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Thread not found"
+            }));
+        }
+        Err(e) => {
+            eprintln!("Database error fetching thread: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch thread"
+            }));
+        }
+    };
+
+    if !thread.is_open {
+        return HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Thread is already closed"
+        }));
+    }
+
+    let close_at = chrono::Utc::now() + delay;
+    let scheduled_by_id = close_data.map(|c| c.closed_by_id.as_str());
+    let scheduled_by_tag = close_data.map(|c| c.closed_by_tag.as_str());
+    let reason = close_data.and_then(|c| c.reason.as_deref());
+
+    // Scheduling again replaces the pending close
+    let scheduled_result = sqlx::query_as::<_, db::ScheduledClose>(
+        r#"
+        INSERT INTO scheduled_closes (thread_id, close_at, scheduled_by_id, scheduled_by_tag, reason)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (thread_id) DO UPDATE
+        SET close_at = EXCLUDED.close_at,
+            scheduled_by_id = EXCLUDED.scheduled_by_id,
+            scheduled_by_tag = EXCLUDED.scheduled_by_tag,
+            reason = EXCLUDED.reason,
+            created_at = NOW()
+        RETURNING *
+        "#,
+    )
+    .bind(thread_id)
+    .bind(close_at)
+    .bind(scheduled_by_id)
+    .bind(scheduled_by_tag)
+    .bind(reason)
+    .fetch_one(&mut *tx)
+    .await;
+
+    let scheduled = match scheduled_result {
+        Ok(scheduled) => scheduled,
+        Err(e) => {
+            eprintln!("Database error scheduling thread close: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to schedule thread close"
+            }));
+        }
+    };
+
+    let event_result = thread_events::record_event(
+        &mut *tx,
+        thread_id,
+        thread_events::EVENT_CLOSE_SCHEDULED,
+        scheduled_by_id,
+        scheduled_by_tag,
+        reason,
+        Some(serde_json::json!({ "close_at": scheduled.close_at.timestamp() })),
+    )
+    .await;
+
+    if let Err(e) = event_result {
+        eprintln!("Database error recording close scheduled event: {}", e);
+        return HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to schedule thread close"
+        }));
+    }
+
+    if let Err(e) = tx.commit().await {
+        eprintln!("Database error committing scheduled close: {}", e);
+        return HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to schedule thread close"
+        }));
+    }
+
+    HttpResponse::Ok().json(scheduled)
+}
+
+#[delete("/threads/{id}/close")]
+async fn cancel_scheduled_close(
+    pool: web::Data<PgPool>,
+    thread_id: web::Path<i32>,
+    cancel_data: Option<web::Json<CancelScheduledClose>>,
+) -> Result<impl Responder> {
+    let thread_id = thread_id.into_inner();
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Database error starting transaction: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to cancel scheduled close"
+            })));
+        }
+    };
+
+    let delete_result = sqlx::query_scalar::<_, i32>(
+        "DELETE FROM scheduled_closes WHERE thread_id = $1 RETURNING thread_id",
+    )
+    .bind(thread_id)
+    .fetch_optional(&mut *tx)
+    .await;
+
+    match delete_result {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "success": false,
+                "message": "No close is scheduled for this thread"
+            })));
+        }
+        Err(e) => {
+            eprintln!("Database error cancelling scheduled close: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to cancel scheduled close"
+            })));
+        }
+    }
+
+    let event_result = thread_events::record_event(
+        &mut *tx,
+        thread_id,
+        thread_events::EVENT_CLOSE_CANCELLED,
+        cancel_data.as_ref().map(|c| c.cancelled_by_id.as_str()),
+        cancel_data.as_ref().map(|c| c.cancelled_by_tag.as_str()),
+        None,
+        None,
+    )
+    .await;
+
+    if let Err(e) = event_result {
+        eprintln!("Database error recording close cancelled event: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to cancel scheduled close"
+        })));
+    }
+
+    if let Err(e) = tx.commit().await {
+        eprintln!(
+            "Database error committing scheduled close cancellation: {}",
+            e
+        );
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to cancel scheduled close"
+        })));
+    }
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "success": true,
+        "message": "Scheduled close cancelled"
+    })))
+}
+
+// Drops a pending close when the thread's own user writes in. Returns whether one was cancelled.
+pub async fn cancel_on_user_activity(
+    conn: &mut PgConnection,
+    thread_id: i32,
+    author_id: &str,
+) -> Result<bool, sqlx::Error> {
+    let cancelled = sqlx::query_scalar::<_, i32>(
+        r#"
+        DELETE FROM scheduled_closes sc
+        USING threads t
+        WHERE sc.thread_id = $1 AND t.id = sc.thread_id AND t.user_id = $2
+        RETURNING sc.thread_id
+        "#,
+    )
+    .bind(thread_id)
+    .bind(author_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    if cancelled.is_none() {
+        return Ok(false);
+    }
+
+    thread_events::record_event(
+        &mut *conn,
+        thread_id,
+        thread_events::EVENT_CLOSE_CANCELLED,
+        Some(author_id),
+        None,
+        Some("User replied"),
+        None,
+    )
+    .await?;
+
+    Ok(true)
+}
+
+// Claims the due schedule and closes the thread in one transaction, so a schedule cancelled
+// or replaced after the candidate query ran is never acted on. Returns None when there was
+// nothing to close.
+async fn close_due_thread(
+    pool: &PgPool,
+    thread_id: i32,
+) -> Result<Option<(db::Thread, db::ScheduledClose)>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    // Lock the thread first, in the same order as close/reopen and user replies
+    let thread = sqlx::query_as::<_, db::Thread>("SELECT * FROM threads WHERE id = $1 FOR UPDATE")
+        .bind(thread_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+    let thread = match thread {
+        Some(thread) => thread,
+        None => return Ok(None),
+    };
+
+    let scheduled = sqlx::query_as::<_, db::ScheduledClose>(
+        "DELETE FROM scheduled_closes WHERE thread_id = $1 AND close_at <= NOW() RETURNING *",
+    )
+    .bind(thread_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let scheduled = match scheduled {
+        Some(scheduled) => scheduled,
+        None => return Ok(None),
+    };
+
+    // Thread was closed by hand; the claim above already dropped the stale schedule
+    if !thread.is_open {
+        tx.commit().await?;
+        return Ok(None);
+    }
+
+    threads::close_locked_thread(
+        &mut tx,
+        thread_id,
+        scheduled.scheduled_by_id.as_deref(),
+        scheduled.scheduled_by_tag.as_deref(),
+        scheduled.reason.as_deref(),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Some((thread, scheduled)))
+}
+
+// Function to execute due scheduled closes in background
+pub async fn run_due_closes(pool: &PgPool) {
+    let due_result = sqlx::query_scalar::<_, i32>(
+        "SELECT thread_id FROM scheduled_closes WHERE close_at <= NOW() ORDER BY close_at",
+    )
+    .fetch_all(pool)
+    .await;
+
+    let due = match due_result {
+        Ok(due) => due,
+        Err(e) => {
+            eprintln!("Background scheduled close query failed: {}", e);
+            return;
+        }
+    };
+
+    for thread_id in due {
+        match close_due_thread(pool, thread_id).await {
+            Ok(Some((thread, scheduled))) => threads::send_thread_closed_webhook(
+                &thread,
+                scheduled
+                    .scheduled_by_id
+                    .as_deref()
+                    .unwrap_or(SYSTEM_ACTOR_ID),
+                scheduled
+                    .scheduled_by_tag
+                    .as_deref()
+                    .unwrap_or(SYSTEM_ACTOR_TAG),
+            ),
+            Ok(None) => {}
+            Err(e) => {
+                eprintln!(
+                    "Background scheduled close of thread {} failed: {}",
+                    thread_id, e
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_duration_accepts_each_unit() {
+        assert_eq!(parse_duration("90s"), Some(chrono::Duration::seconds(90)));
+        assert_eq!(parse_duration("30m"), Some(chrono::Duration::minutes(30)));
+        assert_eq!(parse_duration("24h"), Some(chrono::Duration::hours(24)));
+        assert_eq!(parse_duration(" 7d "), Some(chrono::Duration::days(7)));
+    }
+
+    #[test]
+    fn parse_duration_accepts_the_maximum() {
+        assert_eq!(parse_duration("90d"), Some(chrono::Duration::days(90)));
+    }
+
+    #[test]
+    fn parse_duration_rejects_zero_and_negative() {
+        assert_eq!(parse_duration("0s"), None);
+        assert_eq!(parse_duration("0d"), None);
+        assert_eq!(parse_duration("-5m"), None);
+    }
+
+    #[test]
+    fn parse_duration_rejects_overflow() {
+        assert_eq!(parse_duration("91d"), None);
+        assert_eq!(parse_duration("9223372036854775807d"), None);
+        assert_eq!(parse_duration("99999999999999999999s"), None);
+    }
+
+    #[test]
+    fn parse_duration_rejects_garbage() {
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("h"), None);
+        assert_eq!(parse_duration("24"), None);
+        assert_eq!(parse_duration("24w"), None);
+        assert_eq!(parse_duration("1.5h"), None);
+        assert_eq!(parse_duration("tenm"), None);
+        assert_eq!(parse_duration("24 h"), None);
+        assert_eq!(parse_duration("5µ"), None);
+    }
+}
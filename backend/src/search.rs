@@ -0,0 +1,261 @@
+use crate::auth;
+use crate::db;
+use crate::messages::KIND_STAFF_ANONYMOUS_REPLY;
+use crate::threads::{split_list, VALID_URGENCIES};
+use actix_web::{get, web, HttpRequest, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, QueryBuilder};
+use std::collections::HashMap;
+
+#[derive(Deserialize)]
+struct SearchQuery {
+    q: Option<String>,
+    page: Option<i64>,
+    limit: Option<i64>,
+    is_open: Option<bool>,
+    urgency: Option<String>,
+    author_id: Option<String>,
+    created_after: Option<chrono::DateTime<chrono::Utc>>,
+    created_before: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(sqlx::FromRow)]
+struct SearchHit {
+    thread_id: i32,
+    source: String,
+    snippet: String,
+    match_count: i64,
+    rank: f32,
+}
+
+#[derive(Serialize)]
+struct SearchResult {
+    thread: db::Thread,
+    source: String,
+    snippet: String,
+    match_count: i64,
+    rank: f32,
+}
+
+// Pushes the filters that apply to individual messages and notes
+fn push_hit_filters(
+    builder: &mut QueryBuilder<'_, Postgres>,
+    alias: &str,
+    query: &SearchQuery,
+    is_admin: bool,
+) {
+    if let Some(author_id) = &query.author_id {
+        builder
+            .push(format!(" AND {}.author_id = ", alias))
+            .push_bind(author_id.clone());
+        // Filtering by author would otherwise reveal who wrote anonymous replies
+        if alias == "m" && !is_admin {
+            builder
+                .push(" AND m.kind != ")
+                .push_bind(KIND_STAFF_ANONYMOUS_REPLY);
+        }
+    }
+    if let Some(created_after) = query.created_after {
+        builder
+            .push(format!(" AND {}.created_at >= ", alias))
+            .push_bind(created_after);
+    }
+    if let Some(created_before) = query.created_before {
+        builder
+            .push(format!(" AND {}.created_at < ", alias))
+            .push_bind(created_before);
+    }
+}
+
+// Builds the CTEs ranking every matching message and note, keeping the best hit per thread.
+// Shared by the page and count queries.
+fn push_ranked_hits(
+    builder: &mut QueryBuilder<'_, Postgres>,
+    query: &SearchQuery,
+    terms: &str,
+    is_admin: bool,
+) {
+    builder
+        .push("WITH q AS (SELECT websearch_to_tsquery('english', ")
+        .push_bind(terms.to_string())
+        .push(") AS query), hits AS (");
+
+    builder.push(
+        " SELECT tm.thread_id, 'message' AS source, m.content, m.created_at, \
+         ts_rank(m.content_tsv, q.query) AS rank \
+         FROM messages m \
+         INNER JOIN thread_messages tm ON tm.message_id = m.id \
+         CROSS JOIN q \
+         WHERE m.content_tsv @@ q.query",
+    );
+    push_hit_filters(builder, "m", query, is_admin);
+
+    builder.push(
+        " UNION ALL \
+         SELECT n.thread_id, 'note' AS source, n.content, n.created_at, \
+         ts_rank(n.content_tsv, q.query) AS rank \
+         FROM notes n \
+         CROSS JOIN q \
+         WHERE n.content_tsv @@ q.query \
+         AND n.deleted_at IS NULL",
+    );
+    push_hit_filters(builder, "n", query, is_admin);
+
+    builder.push(
+        "), ranked AS ( \
+         SELECT h.*, \
+         COUNT(*) OVER (PARTITION BY h.thread_id) AS match_count, \
+         ROW_NUMBER() OVER (PARTITION BY h.thread_id ORDER BY h.rank DESC, h.created_at DESC) AS rn \
+         FROM hits h)",
+    );
+}
+
+// Pushes the filters that apply to the thread a hit belongs to
+fn push_thread_filters(
+    builder: &mut QueryBuilder<'_, Postgres>,
+    query: &SearchQuery,
+    urgencies: &[String],
+) {
+    builder.push(" WHERE r.rn = 1");
+
+    if let Some(is_open) = query.is_open {
+        builder.push(" AND t.is_open = ").push_bind(is_open);
+    }
+    if !urgencies.is_empty() {
+        builder
+            .push(" AND t.urgency = ANY(")
+            .push_bind(urgencies.to_vec())
+            .push(")");
+    }
+}
+
+#[get("/search")]
+async fn search(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    query: web::Query<SearchQuery>,
+) -> impl Responder {
+    let terms = query.q.as_deref().map(str::trim).unwrap_or("");
+    if terms.is_empty() {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Search query `q` is required"
+        }));
+    }
+
+    let urgencies = split_list(query.urgency.as_deref());
+    if urgencies
+        .iter()
+        .any(|u| !VALID_URGENCIES.contains(&u.as_str()))
+    {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Invalid urgency level. Must be one of: Low, Medium, High, Urgent"
+        }));
+    }
+
+    let is_admin = auth::is_admin(&req);
+    let page = query.page.unwrap_or(1).max(1);
+    let limit = query.limit.unwrap_or(20).clamp(1, 100); // Max 100, min 1
+    let offset = (page - 1) * limit;
+
+    // Snippets are HTML with <mark> highlights, so escape the content before highlighting it
+    let mut hits_builder = QueryBuilder::<Postgres>::new("");
+    push_ranked_hits(&mut hits_builder, &query, terms, is_admin);
+    hits_builder.push(
+        " SELECT r.thread_id, r.source, r.match_count, r.rank, \
+         ts_headline('english', \
+         replace(replace(replace(r.content, '&', '&amp;'), '<', '&lt;'), '>', '&gt;'), \
+         q.query, 'StartSel=<mark>, StopSel=</mark>, MaxFragments=2, MaxWords=30, MinWords=10') AS snippet \
+         FROM ranked r \
+         INNER JOIN threads t ON t.id = r.thread_id \
+         CROSS JOIN q",
+    );
+    push_thread_filters(&mut hits_builder, &query, &urgencies);
+    hits_builder
+        .push(" ORDER BY r.rank DESC, t.id DESC LIMIT ")
+        .push_bind(limit)
+        .push(" OFFSET ")
+        .push_bind(offset);
+
+    let hits_result = hits_builder
+        .build_query_as::<SearchHit>()
+        .fetch_all(pool.get_ref())
+        .await;
+
+    let hits = match hits_result {
+        Ok(hits) => hits,
+        Err(e) => {
+            eprintln!("Database error searching: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to search"
+            }));
+        }
+    };
+
+    let mut count_builder = QueryBuilder::<Postgres>::new("");
+    push_ranked_hits(&mut count_builder, &query, terms, is_admin);
+    count_builder.push(
+        " SELECT COUNT(*) FROM ranked r \
+         INNER JOIN threads t ON t.id = r.thread_id",
+    );
+    push_thread_filters(&mut count_builder, &query, &urgencies);
+
+    let total_count_result: Result<i64, sqlx::Error> = count_builder
+        .build_query_scalar()
+        .fetch_one(pool.get_ref())
+        .await;
+
+    let total_count = match total_count_result {
+        Ok(count) => count,
+        Err(e) => {
+            eprintln!("Database error counting search results: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to search"
+            }));
+        }
+    };
+
+    let thread_ids: Vec<i32> = hits.iter().map(|hit| hit.thread_id).collect();
+    let threads_result =
+        sqlx::query_as::<_, db::Thread>("SELECT * FROM threads WHERE id = ANY($1)")
+            .bind(&thread_ids)
+            .fetch_all(pool.get_ref())
+            .await;
+
+    let mut threads_by_id: HashMap<i32, db::Thread> = match threads_result {
+        Ok(threads) => threads.into_iter().map(|t| (t.id, t)).collect(),
+        Err(e) => {
+            eprintln!("Database error fetching threads: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch threads"
+            }));
+        }
+    };
+
+    let results: Vec<SearchResult> = hits
+        .into_iter()
+        .filter_map(|hit| {
+            let thread = threads_by_id.remove(&hit.thread_id)?;
+            Some(SearchResult {
+                thread,
+                source: hit.source,
+                snippet: hit.snippet,
+                match_count: hit.match_count,
+                rank: hit.rank,
+            })
+        })
+        .collect();
+
+    let total_pages = (total_count + limit - 1) / limit;
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "results": results,
+        "pagination": {
+            "page": page,
+            "limit": limit,
+            "total_count": total_count,
+            "total_pages": total_pages,
+            "has_next": page < total_pages,
+            "has_prev": page > 1
+        }
+    }))
+}
@@ -0,0 +1,68 @@
+use async_trait::async_trait;
+use std::io;
+use std::path::PathBuf;
+
+// Where archived attachment bytes live. Keys are attachment UUIDs, so implementations never
+// see user-controlled paths. An S3-compatible store only needs to implement these two calls.
+#[async_trait]
+pub trait AttachmentStore: Send + Sync {
+    async fn put(&self, key: &str, bytes: &[u8]) -> io::Result<()>;
+    async fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>>;
+    // Removing a key that doesn't exist is not an error
+    async fn delete(&self, key: &str) -> io::Result<()>;
+}
+
+// Best-effort cleanup after the rows are gone; a file we fail to remove is only logged
+pub async fn delete_keys(store: &dyn AttachmentStore, keys: &[String]) {
+    for key in keys {
+        if let Err(e) = store.delete(key).await {
+            eprintln!("Storage error deleting attachment {}: {}", key, e);
+        }
+    }
+}
+
+pub struct LocalStore {
+    root: PathBuf,
+}
+
+impl LocalStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    // Fan out by key prefix so a single directory doesn't collect every file
+    fn path_for(&self, key: &str) -> PathBuf {
+        let prefix = key.get(..2).unwrap_or(key);
+        self.root.join(prefix).join(key)
+    }
+}
+
+#[async_trait]
+impl AttachmentStore for LocalStore {
+    async fn put(&self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        let path = self.path_for(key);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        // Write to a temp file first so readers never see a partial attachment
+        let tmp_path = path.with_extension("tmp");
+        tokio::fs::write(&tmp_path, bytes).await?;
+        tokio::fs::rename(&tmp_path, &path).await
+    }
+
+    async fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        match tokio::fs::read(self.path_for(key)).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> io::Result<()> {
+        match tokio::fs::remove_file(self.path_for(key)).await {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
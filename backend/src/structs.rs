@@ -1,11 +1,30 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct CreateAttachment {
+    pub url: String,
+    pub filename: String,
+    pub content_type: Option<String>,
+    pub size: i64,
+    pub discord_id: Option<String>,
+}
 
 #[derive(Deserialize)]
 pub struct CreateMessage {
     pub author_id: String,
     pub author_tag: String,
     pub content: String,
-    pub attachments: Option<serde_json::Value>,
+    pub attachments: Option<Vec<CreateAttachment>>,
+    pub discord_message_id: Option<String>,
+    pub kind: Option<String>,
+    pub anonymous: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct EditMessage {
+    pub content: String,
+    pub attachments: Option<Vec<CreateAttachment>>,
+    pub edited_by_id: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -27,6 +46,14 @@ pub struct CreateNote {
     pub author_id: String,
     pub author_tag: String,
     pub content: String,
+    pub pinned: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct EditNote {
+    pub content: Option<String>,
+    pub pinned: Option<bool>,
+    pub edited_by_id: String,
 }
 
 #[derive(Deserialize)]
@@ -42,9 +69,88 @@ pub struct CreateBlockedUser {
 pub struct CloseThread {
     pub closed_by_id: String,
     pub closed_by_tag: String,
+    pub reason: Option<String>,
+    pub notify: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct CancelScheduledClose {
+    pub cancelled_by_id: String,
+    pub cancelled_by_tag: String,
+}
+
+#[derive(Deserialize)]
+pub struct ReopenThread {
+    pub reopened_by_id: String,
+    pub reopened_by_tag: String,
+    pub reason: Option<String>,
 }
 
 #[derive(Deserialize)]
 pub struct UpdateThreadUrgency {
     pub urgency: String,
+    pub changed_by_id: Option<String>,
+    pub changed_by_tag: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateThreadStatus {
+    pub status: String,
+    pub changed_by_id: Option<String>,
+    pub changed_by_tag: Option<String>,
+    pub reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateThreadChannel {
+    pub thread_id: String,
+    pub changed_by_id: Option<String>,
+    pub changed_by_tag: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct AssignThread {
+    pub assignee_id: String,
+    pub assignee_tag: String,
+    pub assigned_by_id: Option<String>,
+    pub assigned_by_tag: Option<String>,
+    pub force: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct UnassignThread {
+    pub unassigned_by_id: String,
+    pub unassigned_by_tag: String,
+}
+
+#[derive(Deserialize)]
+pub struct CreateTag {
+    pub name: String,
+    pub color: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateTag {
+    pub name: Option<String>,
+    pub color: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct SetThreadTags {
+    pub tag_ids: Vec<i32>,
+}
+
+#[derive(Deserialize)]
+pub struct MergeThread {
+    pub source_thread_id: i32,
+    pub merged_by_id: Option<String>,
+    pub merged_by_tag: Option<String>,
+    pub force: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct AnonymizeUser {
+    pub requested_by_id: String,
+    pub requested_by_tag: String,
+    pub reason: Option<String>,
 }
@@ -0,0 +1,345 @@
+use crate::db;
+use crate::structs::{CreateTag, SetThreadTags, UpdateTag};
+use actix_web::{delete, get, post, put, web, HttpResponse, Responder, Result};
+use sqlx::PgPool;
+
+const MAX_TAG_NAME_LENGTH: usize = 64; // tags.name is VARCHAR(64)
+
+// Colours are stored as #RRGGBB so the dashboard and embeds can use them directly
+fn is_valid_color(color: &str) -> bool {
+    color.len() == 7 && color.starts_with('#') && color[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn tag_conflict_response(db_err: &dyn sqlx::error::DatabaseError) -> Option<HttpResponse> {
+    match db_err.constraint() {
+        Some("tags_name_key") => Some(HttpResponse::Conflict().json(serde_json::json!({
+            "error": "A tag with this name already exists"
+        }))),
+        Some("chk_tag_color_format") => Some(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Invalid color. Must be a hex color like #FF8800"
+        }))),
+        _ => None,
+    }
+}
+
+#[get("/tags")]
+async fn get_tags(pool: web::Data<PgPool>) -> impl Responder {
+    let tags_result = sqlx::query_as::<_, db::Tag>("SELECT * FROM tags ORDER BY name")
+        .fetch_all(pool.get_ref())
+        .await;
+
+    match tags_result {
+        Ok(tags) => HttpResponse::Ok().json(tags),
+        Err(e) => {
+            eprintln!("Database error fetching tags: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch tags"
+            }))
+        }
+    }
+}
+
+#[post("/tags")]
+async fn create_tag(
+    pool: web::Data<PgPool>,
+    tag_data: web::Json<CreateTag>,
+) -> Result<impl Responder> {
+    let name = tag_data.name.trim();
+    if name.is_empty() {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Tag name cannot be empty"
+        })));
+    }
+    if name.chars().count() > MAX_TAG_NAME_LENGTH {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Tag name must be at most 64 characters"
+        })));
+    }
+
+    if let Some(color) = &tag_data.color {
+        if !is_valid_color(color) {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid color. Must be a hex color like #FF8800"
+            })));
+        }
+    }
+
+    let new_tag_result =
+        sqlx::query_as::<_, db::Tag>("INSERT INTO tags (name, color) VALUES ($1, $2) RETURNING *")
+            .bind(name)
+            .bind(&tag_data.color)
+            .fetch_one(pool.get_ref())
+            .await;
+
+    match new_tag_result {
+        Ok(new_tag) => Ok(HttpResponse::Ok().json(new_tag)),
+        Err(sqlx::Error::Database(db_err)) => match tag_conflict_response(db_err.as_ref()) {
+            Some(response) => Ok(response),
+            None => {
+                eprintln!("Database error creating tag: {}", db_err);
+                Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                    "error": "Failed to create tag"
+                })))
+            }
+        },
+        Err(e) => {
+            eprintln!("Database error creating tag: {}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to create tag"
+            })))
+        }
+    }
+}
+
+#[put("/tags/{id}")]
+async fn update_tag(
+    pool: web::Data<PgPool>,
+    tag_id: web::Path<i32>,
+    tag_data: web::Json<UpdateTag>,
+) -> Result<impl Responder> {
+    let name = tag_data.name.as_deref().map(str::trim);
+    if name == Some("") {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Tag name cannot be empty"
+        })));
+    }
+    if name.is_some_and(|name| name.chars().count() > MAX_TAG_NAME_LENGTH) {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Tag name must be at most 64 characters"
+        })));
+    }
+
+    // An empty color string clears the colour
+    if let Some(color) = tag_data.color.as_deref().filter(|c| !c.is_empty()) {
+        if !is_valid_color(color) {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid color. Must be a hex color like #FF8800"
+            })));
+        }
+    }
+
+    let updated_tag_result = sqlx::query_as::<_, db::Tag>(
+        r#"
+        UPDATE tags
+        SET name = COALESCE($2, name),
+            color = CASE WHEN $3::TEXT IS NULL THEN color WHEN $3 = '' THEN NULL ELSE $3 END
+        WHERE id = $1
+        RETURNING *
+        "#,
+    )
+    .bind(tag_id.into_inner())
+    .bind(name)
+    .bind(&tag_data.color)
+    .fetch_one(pool.get_ref())
+    .await;
+
+    match updated_tag_result {
+        Ok(updated_tag) => Ok(HttpResponse::Ok().json(updated_tag)),
+        Err(sqlx::Error::RowNotFound) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Tag not found"
+        }))),
+        Err(sqlx::Error::Database(db_err)) => match tag_conflict_response(db_err.as_ref()) {
+            Some(response) => Ok(response),
+            None => {
+                eprintln!("Database error updating tag: {}", db_err);
+                Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                    "error": "Failed to update tag"
+                })))
+            }
+        },
+        Err(e) => {
+            eprintln!("Database error updating tag: {}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to update tag"
+            })))
+        }
+    }
+}
+
+#[delete("/tags/{id}")]
+async fn delete_tag(pool: web::Data<PgPool>, tag_id: web::Path<i32>) -> impl Responder {
+    // thread_tags rows go with it via ON DELETE CASCADE
+    let delete_result = sqlx::query("DELETE FROM tags WHERE id = $1")
+        .bind(tag_id.into_inner())
+        .execute(pool.get_ref())
+        .await;
+
+    match delete_result {
+        Ok(result) => {
+            if result.rows_affected() > 0 {
+                HttpResponse::Ok().json(serde_json::json!({
+                    "success": true,
+                    "message": "Tag deleted successfully"
+                }))
+            } else {
+                HttpResponse::NotFound().json(serde_json::json!({
+                    "success": false,
+                    "message": "Tag not found"
+                }))
+            }
+        }
+        Err(e) => {
+            eprintln!("Database error deleting tag: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to delete tag"
+            }))
+        }
+    }
+}
+
+#[get("/threads/{id}/tags")]
+async fn get_thread_tags(pool: web::Data<PgPool>, thread_id: web::Path<i32>) -> impl Responder {
+    let tags_result = sqlx::query_as::<_, db::Tag>(
+        r#"
+        SELECT tg.*
+        FROM tags tg
+        INNER JOIN thread_tags tt ON tt.tag_id = tg.id
+        WHERE tt.thread_id = $1
+        ORDER BY tg.name
+        "#,
+    )
+    .bind(thread_id.into_inner())
+    .fetch_all(pool.get_ref())
+    .await;
+
+    match tags_result {
+        Ok(tags) => HttpResponse::Ok().json(tags),
+        Err(e) => {
+            eprintln!("Database error fetching thread tags: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch thread tags"
+            }))
+        }
+    }
+}
+
+#[put("/threads/{id}/tags")]
+async fn set_thread_tags(
+    pool: web::Data<PgPool>,
+    thread_id: web::Path<i32>,
+    tags_data: web::Json<SetThreadTags>,
+) -> Result<impl Responder> {
+    let thread_id = thread_id.into_inner();
+
+    let mut tag_ids = tags_data.tag_ids.clone();
+    tag_ids.sort_unstable();
+    tag_ids.dedup();
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Database error starting transaction: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to update thread tags"
+            })));
+        }
+    };
+
+    let thread_exists_result: Result<bool, sqlx::Error> =
+        sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM threads WHERE id = $1)")
+            .bind(thread_id)
+            .fetch_one(&mut *tx)
+            .await;
+
+    match thread_exists_result {
+        Ok(true) => {}
+        Ok(false) => {
+            return Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Thread not found"
+            })));
+        }
+        Err(e) => {
+            eprintln!("Database error fetching thread: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to update thread tags"
+            })));
+        }
+    }
+
+    let known_count_result: Result<i64, sqlx::Error> =
+        sqlx::query_scalar("SELECT COUNT(*) FROM tags WHERE id = ANY($1)")
+            .bind(&tag_ids)
+            .fetch_one(&mut *tx)
+            .await;
+
+    match known_count_result {
+        Ok(count) if count == tag_ids.len() as i64 => {}
+        Ok(_) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "One or more tags do not exist"
+            })));
+        }
+        Err(e) => {
+            eprintln!("Database error validating tags: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to update thread tags"
+            })));
+        }
+    }
+
+    // Replace the whole set; tags that stay keep their original created_at
+    let delete_result =
+        sqlx::query("DELETE FROM thread_tags WHERE thread_id = $1 AND NOT (tag_id = ANY($2))")
+            .bind(thread_id)
+            .bind(&tag_ids)
+            .execute(&mut *tx)
+            .await;
+
+    if let Err(e) = delete_result {
+        eprintln!("Database error removing thread tags: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to update thread tags"
+        })));
+    }
+
+    let insert_result = sqlx::query(
+        r#"
+        INSERT INTO thread_tags (thread_id, tag_id)
+        SELECT $1, UNNEST($2::INTEGER[])
+        ON CONFLICT (thread_id, tag_id) DO NOTHING
+        "#,
+    )
+    .bind(thread_id)
+    .bind(&tag_ids)
+    .execute(&mut *tx)
+    .await;
+
+    if let Err(e) = insert_result {
+        eprintln!("Database error adding thread tags: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to update thread tags"
+        })));
+    }
+
+    let tags_result = sqlx::query_as::<_, db::Tag>(
+        r#"
+        SELECT tg.*
+        FROM tags tg
+        INNER JOIN thread_tags tt ON tt.tag_id = tg.id
+        WHERE tt.thread_id = $1
+        ORDER BY tg.name
+        "#,
+    )
+    .bind(thread_id)
+    .fetch_all(&mut *tx)
+    .await;
+
+    let tags = match tags_result {
+        Ok(tags) => tags,
+        Err(e) => {
+            eprintln!("Database error fetching thread tags: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to update thread tags"
+            })));
+        }
+    };
+
+    if let Err(e) = tx.commit().await {
+        eprintln!("Database error committing thread tags: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to update thread tags"
+        })));
+    }
+
+    Ok(HttpResponse::Ok().json(tags))
+}
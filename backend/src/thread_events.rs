@@ -0,0 +1,59 @@
+use crate::db;
+use actix_web::{get, web, HttpResponse, Responder};
+use sqlx::{PgExecutor, PgPool};
+
+pub const EVENT_OPEN: &str = "open";
+pub const EVENT_CLOSE: &str = "close";
+pub const EVENT_REOPEN: &str = "reopen";
+pub const EVENT_STATUS_CHANGE: &str = "status_change";
+pub const EVENT_URGENCY_CHANGE: &str = "urgency_change";
+pub const EVENT_CLOSE_SCHEDULED: &str = "close_scheduled";
+pub const EVENT_CLOSE_CANCELLED: &str = "close_cancelled";
+pub const EVENT_MERGE: &str = "merge";
+pub const EVENT_CHANNEL_CHANGE: &str = "channel_change";
+
+pub async fn record_event<'e, E: PgExecutor<'e>>(
+    executor: E,
+    thread_id: i32,
+    event_type: &str,
+    actor_id: Option<&str>,
+    actor_tag: Option<&str>,
+    reason: Option<&str>,
+    metadata: Option<serde_json::Value>,
+) -> Result<db::ThreadEvent, sqlx::Error> {
+    sqlx::query_as::<_, db::ThreadEvent>(
+        r#"
+        INSERT INTO thread_events (thread_id, event_type, actor_id, actor_tag, reason, metadata)
+        VALUES ($1, $2, $3, $4, $5, COALESCE($6, '{}'::jsonb))
+        RETURNING *
+        "#,
+    )
+    .bind(thread_id)
+    .bind(event_type)
+    .bind(actor_id)
+    .bind(actor_tag)
+    .bind(reason)
+    .bind(metadata)
+    .fetch_one(executor)
+    .await
+}
+
+#[get("/threads/{id}/events")]
+async fn get_thread_events(pool: web::Data<PgPool>, thread_id: web::Path<i32>) -> impl Responder {
+    let events_result = sqlx::query_as::<_, db::ThreadEvent>(
+        "SELECT * FROM thread_events WHERE thread_id = $1 ORDER BY created_at ASC, id ASC",
+    )
+    .bind(thread_id.into_inner())
+    .fetch_all(pool.get_ref())
+    .await;
+
+    match events_result {
+        Ok(events) => HttpResponse::Ok().json(events),
+        Err(e) => {
+            eprintln!("Database error fetching thread events: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch thread events"
+            }))
+        }
+    }
+}
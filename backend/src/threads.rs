@@ -1,32 +1,243 @@
+use crate::attachments;
+use crate::auth;
 use crate::db;
-use crate::structs::{CloseThread, CreateMessage, CreateThread, UpdateThreadUrgency};
-use actix_web::{get, post, put, web, HttpResponse, Responder, Result};
+use crate::messages;
+use crate::pagination;
+use crate::scheduled_close;
+use crate::structs::{
+    CloseThread, CreateMessage, CreateThread, ReopenThread, UpdateThreadChannel,
+    UpdateThreadStatus, UpdateThreadUrgency,
+};
+use crate::thread_events;
+use actix_web::{get, post, put, web, HttpRequest, HttpResponse, Responder, Result};
 use serde::Deserialize;
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool, Postgres, QueryBuilder};
 use uuid::Uuid;
 
 #[derive(Deserialize)]
 struct PaginationQuery {
     page: Option<i64>,
     limit: Option<i64>,
+    after: Option<String>,
+    before: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ThreadListQuery {
+    page: Option<i64>,
+    limit: Option<i64>,
+    is_open: Option<bool>,
+    status: Option<String>,
+    urgency: Option<String>,
+    user_id: Option<String>,
+    assignee: Option<String>,
+    tag: Option<String>,
+    created_after: Option<chrono::DateTime<chrono::Utc>>,
+    created_before: Option<chrono::DateTime<chrono::Utc>>,
+    updated_after: Option<chrono::DateTime<chrono::Utc>>,
+    updated_before: Option<chrono::DateTime<chrono::Utc>>,
+    sort: Option<String>,
+    order: Option<String>,
+    after: Option<String>,
+    before: Option<String>,
+}
+
+pub const VALID_URGENCIES: [&str; 4] = ["Low", "Medium", "High", "Urgent"];
+
+const STATUS_WAITING_ON_STAFF: &str = "waiting_on_staff";
+const STATUS_CLOSED: &str = "closed";
+
+const VALID_STATUSES: [&str; 6] = [
+    "open",
+    "waiting_on_user",
+    "waiting_on_staff",
+    "on_hold",
+    "resolved",
+    "closed",
+];
+
+// Statuses reachable through PUT /threads/{id}/status; closing and reopening go through
+// their own endpoints so closer identity and the one-open-thread rule stay enforced
+fn allowed_status_transitions(from: &str) -> &'static [&'static str] {
+    match from {
+        "open" => &["waiting_on_user", "waiting_on_staff", "on_hold", "resolved"],
+        "waiting_on_user" => &["waiting_on_staff", "on_hold", "resolved"],
+        "waiting_on_staff" => &["waiting_on_user", "on_hold", "resolved"],
+        "on_hold" => &["waiting_on_user", "waiting_on_staff", "resolved"],
+        "resolved" => &["waiting_on_user", "waiting_on_staff"],
+        _ => &[],
+    }
+}
+
+const URGENCY_WEIGHT_SQL: &str =
+    "CASE urgency WHEN 'Urgent' THEN 4 WHEN 'High' THEN 3 WHEN 'Medium' THEN 2 WHEN 'Low' THEN 1 ELSE 0 END";
+
+pub fn split_list(value: Option<&str>) -> Vec<String> {
+    value
+        .map(|value| {
+            value
+                .split(',')
+                .map(|item| item.trim().to_string())
+                .filter(|item| !item.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Appends the WHERE clause for the thread list filters; shared by the page and count queries
+fn push_thread_filters(
+    builder: &mut QueryBuilder<'_, Postgres>,
+    query: &ThreadListQuery,
+    statuses: &[String],
+    urgencies: &[String],
+    tags: &[String],
+) {
+    builder.push(" WHERE TRUE");
+
+    if let Some(is_open) = query.is_open {
+        builder.push(" AND is_open = ").push_bind(is_open);
+    }
+    if !statuses.is_empty() {
+        builder
+            .push(" AND status = ANY(")
+            .push_bind(statuses.to_vec())
+            .push(")");
+    }
+    if !urgencies.is_empty() {
+        builder
+            .push(" AND urgency = ANY(")
+            .push_bind(urgencies.to_vec())
+            .push(")");
+    }
+    if let Some(user_id) = &query.user_id {
+        builder.push(" AND user_id = ").push_bind(user_id.clone());
+    }
+    // `assignee=none` lists unclaimed threads
+    match query.assignee.as_deref() {
+        Some("none") => {
+            builder.push(" AND assigned_to_id IS NULL");
+        }
+        Some(assignee) => {
+            builder
+                .push(" AND assigned_to_id = ")
+                .push_bind(assignee.to_string());
+        }
+        None => {}
+    }
+    if !tags.is_empty() {
+        builder
+            .push(
+                " AND EXISTS (SELECT 1 FROM thread_tags tt INNER JOIN tags tg ON tg.id = tt.tag_id \
+                 WHERE tt.thread_id = threads.id AND tg.name = ANY(",
+            )
+            .push_bind(tags.to_vec())
+            .push("))");
+    }
+    if let Some(created_after) = query.created_after {
+        builder.push(" AND created_at >= ").push_bind(created_after);
+    }
+    if let Some(created_before) = query.created_before {
+        builder.push(" AND created_at < ").push_bind(created_before);
+    }
+    if let Some(updated_after) = query.updated_after {
+        builder.push(" AND updated_at >= ").push_bind(updated_after);
+    }
+    if let Some(updated_before) = query.updated_before {
+        builder.push(" AND updated_at < ").push_bind(updated_before);
+    }
 }
 
 #[get("/threads")]
 async fn get_threads(
     pool: web::Data<PgPool>,
-    query: web::Query<PaginationQuery>,
+    query: web::Query<ThreadListQuery>,
 ) -> impl Responder {
     let page = query.page.unwrap_or(1).max(1);
-    let limit = query.limit.unwrap_or(20).min(100).max(1); // Max 100, min 1
+    let limit = query.limit.unwrap_or(20).clamp(1, 100); // Max 100, min 1
     let offset = (page - 1) * limit;
 
-    let threads_result = sqlx::query_as::<_, db::Thread>(
-        "SELECT * FROM threads ORDER BY id DESC LIMIT $1 OFFSET $2",
-    )
-    .bind(limit)
-    .bind(offset)
-    .fetch_all(pool.get_ref())
-    .await;
+    let statuses = split_list(query.status.as_deref());
+
+    // Urgency accepts a comma-separated list, e.g. ?urgency=High,Urgent
+    let urgencies = split_list(query.urgency.as_deref());
+
+    // Tags match by name, any of a comma-separated list, e.g. ?tag=appeal,report
+    let tags = split_list(query.tag.as_deref());
+
+    if statuses
+        .iter()
+        .any(|st| !VALID_STATUSES.contains(&st.as_str()))
+    {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Invalid status. Must be one of: open, waiting_on_user, waiting_on_staff, on_hold, resolved, closed"
+        }));
+    }
+
+    if urgencies
+        .iter()
+        .any(|u| !VALID_URGENCIES.contains(&u.as_str()))
+    {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Invalid urgency level. Must be one of: Low, Medium, High, Urgent"
+        }));
+    }
+
+    let sort_column = match query.sort.as_deref().unwrap_or("id") {
+        "id" => "id",
+        "created_at" => "created_at",
+        "updated_at" => "updated_at",
+        "urgency" => URGENCY_WEIGHT_SQL,
+        _ => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid sort field. Must be one of: id, created_at, updated_at, urgency"
+            }));
+        }
+    };
+
+    let direction = match query.order.as_deref().unwrap_or("desc") {
+        "asc" => "ASC",
+        "desc" => "DESC",
+        _ => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid sort order. Must be one of: asc, desc"
+            }));
+        }
+    };
+
+    if query.after.is_some() || query.before.is_some() {
+        if sort_column != "id" {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Cursor pagination only supports sort=id"
+            }));
+        }
+        return get_threads_by_cursor(
+            pool.get_ref(),
+            &query,
+            &statuses,
+            &urgencies,
+            &tags,
+            direction,
+            limit,
+        )
+        .await;
+    }
+
+    let mut threads_query = QueryBuilder::<Postgres>::new("SELECT * FROM threads");
+    push_thread_filters(&mut threads_query, &query, &statuses, &urgencies, &tags);
+    threads_query
+        .push(format!(
+            " ORDER BY {} {} NULLS LAST, id {}",
+            sort_column, direction, direction
+        ))
+        .push(" LIMIT ")
+        .push_bind(limit)
+        .push(" OFFSET ")
+        .push_bind(offset);
+
+    let threads_result = threads_query
+        .build_query_as::<db::Thread>()
+        .fetch_all(pool.get_ref())
+        .await;
 
     let threads = match threads_result {
         Ok(threads) => threads,
@@ -38,10 +249,13 @@ async fn get_threads(
         }
     };
 
-    let total_count_result: Result<i64, sqlx::Error> =
-        sqlx::query_scalar("SELECT COUNT(*) FROM threads")
-            .fetch_one(pool.get_ref())
-            .await;
+    let mut count_query = QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM threads");
+    push_thread_filters(&mut count_query, &query, &statuses, &urgencies, &tags);
+
+    let total_count_result: Result<i64, sqlx::Error> = count_query
+        .build_query_scalar()
+        .fetch_one(pool.get_ref())
+        .await;
 
     let total_count = match total_count_result {
         Ok(count) => count,
@@ -55,6 +269,14 @@ async fn get_threads(
 
     let total_pages = (total_count + limit - 1) / limit;
 
+    // Lets offset clients switch to cursor mode from any page
+    let next_cursor = match threads.last() {
+        Some(last) if page < total_pages && sort_column == "id" => {
+            Some(pagination::encode_thread_cursor(last.id))
+        }
+        _ => None,
+    };
+
     HttpResponse::Ok().json(serde_json::json!({
         "threads": threads,
         "pagination": {
@@ -63,7 +285,100 @@ async fn get_threads(
             "total_count": total_count,
             "total_pages": total_pages,
             "has_next": page < total_pages,
-            "has_prev": page > 1
+            "has_prev": page > 1,
+            "next_cursor": next_cursor
+        }
+    }))
+}
+
+async fn get_threads_by_cursor(
+    pool: &PgPool,
+    query: &ThreadListQuery,
+    statuses: &[String],
+    urgencies: &[String],
+    tags: &[String],
+    direction: &str,
+    limit: i64,
+) -> HttpResponse {
+    let (cursor, forward) = match (&query.after, &query.before) {
+        (Some(after), None) => (after, true),
+        (None, Some(before)) => (before, false),
+        _ => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Use either after or before, not both"
+            }));
+        }
+    };
+
+    let cursor_id = match pagination::decode_thread_cursor(cursor) {
+        Some(id) => id,
+        None => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid cursor"
+            }));
+        }
+    };
+
+    // `after` walks in the requested order; `before` walks against it and is reversed below
+    let descending = direction == "DESC";
+    let (comparison, scan_direction) = if forward == descending {
+        ("<", "DESC")
+    } else {
+        (">", "ASC")
+    };
+
+    let mut threads_query = QueryBuilder::<Postgres>::new("SELECT * FROM threads");
+    push_thread_filters(&mut threads_query, query, statuses, urgencies, tags);
+    threads_query
+        .push(format!(" AND id {} ", comparison))
+        .push_bind(cursor_id)
+        .push(format!(" ORDER BY id {}", scan_direction))
+        .push(" LIMIT ")
+        .push_bind(limit + 1);
+
+    let threads_result = threads_query
+        .build_query_as::<db::Thread>()
+        .fetch_all(pool)
+        .await;
+
+    let mut threads = match threads_result {
+        Ok(threads) => threads,
+        Err(e) => {
+            eprintln!("Database error fetching threads: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch threads"
+            }));
+        }
+    };
+
+    let has_more = threads.len() as i64 > limit;
+    threads.truncate(limit as usize);
+    if !forward {
+        threads.reverse();
+    }
+
+    let (has_next, has_prev) = if forward {
+        (has_more, true)
+    } else {
+        (true, has_more)
+    };
+    let next_cursor = threads
+        .last()
+        .filter(|_| has_next)
+        .map(|t| pagination::encode_thread_cursor(t.id));
+    let prev_cursor = threads
+        .first()
+        .filter(|_| has_prev)
+        .map(|t| pagination::encode_thread_cursor(t.id));
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "threads": threads,
+        "pagination": {
+            "limit": limit,
+            "has_next": next_cursor.is_some(),
+            "has_prev": prev_cursor.is_some(),
+            "next_cursor": next_cursor,
+            "prev_cursor": prev_cursor
         }
     }))
 }
@@ -75,9 +390,7 @@ async fn create_thread(
 ) -> Result<impl Responder> {
     // Validate urgency level
     let urgency = thread.urgency.as_deref().unwrap_or("Medium");
-    let valid_urgencies = ["Low", "Medium", "High", "Urgent"];
-
-    if !valid_urgencies.contains(&urgency) {
+    if !VALID_URGENCIES.contains(&urgency) {
         return Ok(HttpResponse::BadRequest().json(serde_json::json!({
             "error": "Invalid urgency level. Must be one of: Low, Medium, High, Urgent"
         })));
@@ -90,17 +403,54 @@ async fn create_thread(
         })));
     }
 
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Database error starting transaction: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to create thread"
+            })));
+        }
+    };
+
     let new_thread_result = sqlx::query_as::<_, db::Thread>(
         "INSERT INTO threads (user_id, thread_id, urgency) VALUES ($1, $2, $3) RETURNING *",
     )
     .bind(&thread.user_id)
     .bind(&thread.thread_id)
     .bind(urgency)
-    .fetch_one(pool.get_ref())
+    .fetch_one(&mut *tx)
     .await;
 
     match new_thread_result {
-        Ok(new_thread) => Ok(HttpResponse::Ok().json(new_thread)),
+        Ok(new_thread) => {
+            let event_result = thread_events::record_event(
+                &mut *tx,
+                new_thread.id,
+                thread_events::EVENT_OPEN,
+                Some(&thread.user_id),
+                None,
+                None,
+                None,
+            )
+            .await;
+
+            if let Err(e) = event_result {
+                eprintln!("Database error recording thread open event: {}", e);
+                return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                    "error": "Failed to create thread"
+                })));
+            }
+
+            if let Err(e) = tx.commit().await {
+                eprintln!("Database error committing thread creation: {}", e);
+                return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                    "error": "Failed to create thread"
+                })));
+            }
+
+            Ok(HttpResponse::Ok().json(new_thread))
+        }
         Err(sqlx::Error::Database(db_err)) => {
             // Handle specific constraint violations
             if let Some(constraint) = db_err.constraint() {
@@ -150,6 +500,7 @@ async fn create_thread(
 
 #[get("/threads/{id}")]
 async fn get_thread(
+    req: HttpRequest,
     pool: web::Data<PgPool>,
     thread_id: web::Path<i32>,
     query: web::Query<PaginationQuery>,
@@ -174,8 +525,20 @@ async fn get_thread(
         }
     };
 
+    let limit = query.limit.unwrap_or(50).clamp(1, 100); // Max 100, min 1
+
+    if query.after.is_some() || query.before.is_some() {
+        return get_thread_messages_by_cursor(
+            pool.get_ref(),
+            thread,
+            &query,
+            limit,
+            auth::is_admin(&req),
+        )
+        .await;
+    }
+
     let page = query.page.unwrap_or(1).max(1);
-    let limit = query.limit.unwrap_or(50).min(100).max(1); // Max 100, min 1
     let offset = (page - 1) * limit;
 
     let messages_result = sqlx::query_as::<_, db::Message>(
@@ -184,7 +547,7 @@ async fn get_thread(
         FROM messages m
         INNER JOIN thread_messages tm ON m.id = tm.message_id
         WHERE tm.thread_id = $1 
-        ORDER BY m.created_at ASC 
+        ORDER BY m.created_at ASC, m.id ASC
         LIMIT $2 OFFSET $3
         "#,
     )
@@ -194,7 +557,7 @@ async fn get_thread(
     .fetch_all(pool.get_ref())
     .await;
 
-    let messages = match messages_result {
+    let mut messages = match messages_result {
         Ok(messages) => messages,
         Err(e) => {
             eprintln!("Database error fetching messages: {}", e);
@@ -204,6 +567,14 @@ async fn get_thread(
         }
     };
 
+    if let Err(e) = attachments::attach_to_messages(pool.get_ref(), &mut messages).await {
+        eprintln!("Database error fetching attachments: {}", e);
+        return HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to fetch messages"
+        }));
+    }
+    messages::hide_anonymous_authors(&mut messages, auth::is_admin(&req));
+
     let total_count_result: Result<i64, sqlx::Error> =
         sqlx::query_scalar("SELECT COUNT(*) FROM thread_messages WHERE thread_id = $1")
             .bind(thread.id)
@@ -222,6 +593,14 @@ async fn get_thread(
 
     let total_pages = (total_count + limit - 1) / limit;
 
+    // Lets offset clients switch to cursor mode from any page
+    let next_cursor = match messages.last() {
+        Some(last) if page < total_pages => {
+            Some(pagination::encode_message_cursor(last.created_at, last.id))
+        }
+        _ => None,
+    };
+
     HttpResponse::Ok().json(serde_json::json!({
         "thread": thread,
         "messages": messages,
@@ -231,129 +610,568 @@ async fn get_thread(
             "total_count": total_count,
             "total_pages": total_pages,
             "has_next": page < total_pages,
-            "has_prev": page > 1
+            "has_prev": page > 1,
+            "next_cursor": next_cursor
         }
     }))
 }
 
-#[post("/threads/{id}/close")]
-async fn close_thread(
-    pool: web::Data<PgPool>,
-    thread_id: web::Path<i32>,
-    close_data: Option<web::Json<CloseThread>>,
-) -> Result<impl Responder> {
-    let thread_id = thread_id.into_inner();
+async fn get_thread_messages_by_cursor(
+    pool: &PgPool,
+    thread: db::Thread,
+    query: &PaginationQuery,
+    limit: i64,
+    is_admin: bool,
+) -> HttpResponse {
+    let (cursor, forward) = match (&query.after, &query.before) {
+        (Some(after), None) => (after, true),
+        (None, Some(before)) => (before, false),
+        _ => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Use either after or before, not both"
+            }));
+        }
+    };
 
-    // Get thread info before closing
-    let thread_result = sqlx::query_as::<_, db::Thread>("SELECT * FROM threads WHERE id = $1")
-        .bind(thread_id)
-        .fetch_one(pool.get_ref())
+    let (cursor_created_at, cursor_id) = match pagination::decode_message_cursor(cursor) {
+        Some(position) => position,
+        None => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid cursor"
+            }));
+        }
+    };
+
+    // Messages read oldest first; `before` scans backwards and is reversed below
+    let sql = if forward {
+        r#"
+        SELECT m.*
+        FROM messages m
+        INNER JOIN thread_messages tm ON m.id = tm.message_id
+        WHERE tm.thread_id = $1 AND (m.created_at, m.id) > ($2, $3)
+        ORDER BY m.created_at ASC, m.id ASC
+        LIMIT $4
+        "#
+    } else {
+        r#"
+        SELECT m.*
+        FROM messages m
+        INNER JOIN thread_messages tm ON m.id = tm.message_id
+        WHERE tm.thread_id = $1 AND (m.created_at, m.id) < ($2, $3)
+        ORDER BY m.created_at DESC, m.id DESC
+        LIMIT $4
+        "#
+    };
+
+    let messages_result = sqlx::query_as::<_, db::Message>(sql)
+        .bind(thread.id)
+        .bind(cursor_created_at)
+        .bind(cursor_id)
+        .bind(limit + 1)
+        .fetch_all(pool)
         .await;
 
-    let thread = match thread_result {
-        Ok(thread) => thread,
-        Err(sqlx::Error::RowNotFound) => {
-            return Ok(HttpResponse::NotFound().json(serde_json::json!({
-                "error": "Thread not found"
-            })));
-        }
+    let mut messages = match messages_result {
+        Ok(messages) => messages,
         Err(e) => {
-            eprintln!("Database error fetching thread: {}", e);
-            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to fetch thread"
-            })));
+            eprintln!("Database error fetching messages: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch messages"
+            }));
         }
     };
 
-    let updated_thread_result = sqlx::query_as::<_, db::Thread>(
-        "UPDATE threads SET is_open = FALSE WHERE id = $1 RETURNING *",
+    if let Err(e) = attachments::attach_to_messages(pool, &mut messages).await {
+        eprintln!("Database error fetching attachments: {}", e);
+        return HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to fetch messages"
+        }));
+    }
+
+    let has_more = messages.len() as i64 > limit;
+    messages.truncate(limit as usize);
+    if !forward {
+        messages.reverse();
+    }
+    messages::hide_anonymous_authors(&mut messages, is_admin);
+
+    let (has_next, has_prev) = if forward {
+        (has_more, true)
+    } else {
+        (true, has_more)
+    };
+    let next_cursor = messages
+        .last()
+        .filter(|_| has_next)
+        .map(|m| pagination::encode_message_cursor(m.created_at, m.id));
+    let prev_cursor = messages
+        .first()
+        .filter(|_| has_prev)
+        .map(|m| pagination::encode_message_cursor(m.created_at, m.id));
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "thread": thread,
+        "messages": messages,
+        "pagination": {
+            "limit": limit,
+            "has_next": next_cursor.is_some(),
+            "has_prev": prev_cursor.is_some(),
+            "next_cursor": next_cursor,
+            "prev_cursor": prev_cursor
+        }
+    }))
+}
+
+#[derive(Deserialize)]
+struct ThreadLookupQuery {
+    open: Option<bool>,
+}
+
+#[get("/threads/by-user/{user_id}")]
+async fn get_thread_by_user(
+    pool: web::Data<PgPool>,
+    user_id: web::Path<String>,
+    query: web::Query<ThreadLookupQuery>,
+) -> impl Responder {
+    // Most recent thread first so a user with history resolves to their latest ticket
+    let thread_result = sqlx::query_as::<_, db::Thread>(
+        r#"
+        SELECT * FROM threads
+        WHERE user_id = $1 AND ($2::BOOLEAN IS NULL OR is_open = $2)
+        ORDER BY id DESC
+        LIMIT 1
+        "#,
     )
-    .bind(thread_id)
-    .fetch_one(pool.get_ref())
+    .bind(user_id.into_inner())
+    .bind(query.open)
+    .fetch_optional(pool.get_ref())
     .await;
 
-    let updated_thread = match updated_thread_result {
-        Ok(thread) => thread,
+    match thread_result {
+        Ok(Some(thread)) => HttpResponse::Ok().json(thread),
+        Ok(None) => HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Thread not found"
+        })),
         Err(e) => {
-            eprintln!("Database error closing thread: {}", e);
-            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to close thread"
-            })));
+            eprintln!("Database error fetching thread by user: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch thread"
+            }))
         }
-    };
-
-    if let Some(close_info) = close_data {
-        let discord_webhook_url = std::env::var("DISCORD_WEBHOOK_URL").ok();
+    }
+}
 
-        if let Some(webhook_url) = discord_webhook_url {
-            let payload = serde_json::json!({
-                "type": "thread_closed",
-                "thread": thread,
-                "closed_by_id": close_info.closed_by_id,
-                "closed_by_tag": close_info.closed_by_tag
-            });
+#[get("/threads/by-channel/{thread_id}")]
+async fn get_thread_by_channel(
+    pool: web::Data<PgPool>,
+    channel_id: web::Path<String>,
+) -> impl Responder {
+    let thread_result = sqlx::query_as::<_, db::Thread>(
+        "SELECT * FROM threads WHERE thread_id = $1 ORDER BY id DESC LIMIT 1",
+    )
+    .bind(channel_id.into_inner())
+    .fetch_optional(pool.get_ref())
+    .await;
 
-            // Send webhook to Discord bot in background to avoid blocking
-            tokio::spawn(async move {
-                let client = reqwest::Client::new();
-                if let Err(e) = client.post(&webhook_url).json(&payload).send().await {
-                    eprintln!("Failed to send Discord webhook: {}", e);
-                }
-            });
+    match thread_result {
+        Ok(Some(thread)) => HttpResponse::Ok().json(thread),
+        Ok(None) => HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Thread not found"
+        })),
+        Err(e) => {
+            eprintln!("Database error fetching thread by channel: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch thread"
+            }))
         }
     }
+}
 
-    Ok(HttpResponse::Ok().json(updated_thread))
+pub enum CloseThreadError {
+    NotFound,
+    // Carries the thread as it is, so callers can treat a repeated close as a no-op
+    AlreadyClosed(Box<db::Thread>),
+    Database(sqlx::Error),
 }
 
-#[post("/threads/{id}/messages")]
-async fn add_message_to_thread(
-    pool: web::Data<PgPool>,
-    path: web::Path<i32>,
-    message: web::Json<CreateMessage>,
-) -> Result<impl Responder> {
-    // Validate author ID format (Discord IDs are numeric)
-    if !message.author_id.chars().all(|c| c.is_ascii_digit()) {
-        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
-            "error": "Invalid author ID format"
-        })));
+impl From<sqlx::Error> for CloseThreadError {
+    fn from(e: sqlx::Error) -> Self {
+        CloseThreadError::Database(e)
     }
+}
 
-    let thread_message_id = Uuid::new_v4();
-    let created_at = chrono::Utc::now();
-    let attachments = message
-        .attachments
-        .clone()
-        .unwrap_or_else(|| serde_json::json!([]));
+// Closes a thread the caller has already locked, recording the close event and dropping any
+// pending scheduled close on the same connection
+pub async fn close_locked_thread(
+    conn: &mut PgConnection,
+    thread_id: i32,
+    closed_by_id: Option<&str>,
+    closed_by_tag: Option<&str>,
+    reason: Option<&str>,
+) -> Result<db::Thread, sqlx::Error> {
+    let updated_thread = sqlx::query_as::<_, db::Thread>(
+        r#"
+        UPDATE threads
+        SET is_open = FALSE, status = 'closed', closed_at = NOW(), closed_by_id = $2, closed_by_tag = $3, close_reason = $4
+        WHERE id = $1
+        RETURNING *
+        "#,
+    )
+    .bind(thread_id)
+    .bind(closed_by_id)
+    .bind(closed_by_tag)
+    .bind(reason)
+    .fetch_one(&mut *conn)
+    .await?;
 
-    let new_message_result = sqlx::query_as::<_, db::Message>(
-        "INSERT INTO messages (id, author_id, author_tag, content, created_at, attachments) VALUES ($1, $2, $3, $4, $5, $6) RETURNING *",
+    thread_events::record_event(
+        &mut *conn,
+        thread_id,
+        thread_events::EVENT_CLOSE,
+        closed_by_id,
+        closed_by_tag,
+        reason,
+        None,
     )
-    .bind(&thread_message_id)
-    .bind(&message.author_id)
-    .bind(&message.author_tag)
-    .bind(&message.content)
-    .bind(&created_at)
-    .bind(&attachments)
-    .fetch_one(pool.get_ref())
-    .await;
+    .await?;
 
-    let new_message = match new_message_result {
-        Ok(message) => message,
-        Err(sqlx::Error::Database(db_err)) => {
-            if let Some(constraint) = db_err.constraint() {
-                match constraint {
-                    "chk_author_id_format" => {
-                        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
-                            "error": "Invalid author ID format"
-                        })));
-                    }
-                    _ => {
-                        eprintln!("Database constraint violation: {}", constraint);
-                        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
-                            "error": "Data validation failed"
-                        })));
-                    }
+    // Any pending scheduled close is moot once the thread is closed
+    sqlx::query("DELETE FROM scheduled_closes WHERE thread_id = $1")
+        .bind(thread_id)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(updated_thread)
+}
+
+// Closes a thread and records the close event in one transaction. Returns the thread as it
+// was before closing (what the webhook reports) and after. Every close path goes through
+// close_locked_thread, so they all store the same data.
+pub async fn close_thread_record(
+    pool: &PgPool,
+    thread_id: i32,
+    closed_by_id: Option<&str>,
+    closed_by_tag: Option<&str>,
+    reason: Option<&str>,
+) -> Result<(db::Thread, db::Thread), CloseThreadError> {
+    let mut tx = pool.begin().await?;
+
+    // Lock the row so concurrent close/reopen calls serialize
+    let thread = sqlx::query_as::<_, db::Thread>("SELECT * FROM threads WHERE id = $1 FOR UPDATE")
+        .bind(thread_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(CloseThreadError::NotFound)?;
+
+    if !thread.is_open {
+        return Err(CloseThreadError::AlreadyClosed(Box::new(thread)));
+    }
+
+    let updated_thread =
+        close_locked_thread(&mut tx, thread_id, closed_by_id, closed_by_tag, reason).await?;
+
+    tx.commit().await?;
+
+    Ok((thread, updated_thread))
+}
+
+pub fn send_thread_closed_webhook(thread: &db::Thread, closed_by_id: &str, closed_by_tag: &str) {
+    let discord_webhook_url = std::env::var("DISCORD_WEBHOOK_URL").ok();
+
+    if let Some(webhook_url) = discord_webhook_url {
+        let payload = serde_json::json!({
+            "type": "thread_closed",
+            "thread": thread,
+            "closed_by_id": closed_by_id,
+            "closed_by_tag": closed_by_tag
+        });
+
+        // Send webhook to Discord bot in background to avoid blocking
+        tokio::spawn(async move {
+            let client = reqwest::Client::new();
+            if let Err(e) = client.post(&webhook_url).json(&payload).send().await {
+                eprintln!("Failed to send Discord webhook: {}", e);
+            }
+        });
+    }
+}
+
+#[derive(Deserialize)]
+struct CloseThreadQuery {
+    after: Option<String>,
+}
+
+#[post("/threads/{id}/close")]
+async fn close_thread(
+    pool: web::Data<PgPool>,
+    thread_id: web::Path<i32>,
+    query: web::Query<CloseThreadQuery>,
+    close_data: Option<web::Json<CloseThread>>,
+) -> Result<impl Responder> {
+    let thread_id = thread_id.into_inner();
+
+    // ?after=24h schedules the close instead of closing now
+    if let Some(after) = &query.after {
+        return Ok(scheduled_close::schedule_close(
+            pool.get_ref(),
+            thread_id,
+            after,
+            close_data.as_deref(),
+        )
+        .await);
+    }
+
+    let close_result = close_thread_record(
+        pool.get_ref(),
+        thread_id,
+        close_data.as_ref().map(|c| c.closed_by_id.as_str()),
+        close_data.as_ref().map(|c| c.closed_by_tag.as_str()),
+        close_data.as_ref().and_then(|c| c.reason.as_deref()),
+    )
+    .await;
+
+    let (thread, updated_thread) = match close_result {
+        Ok(threads) => threads,
+        Err(CloseThreadError::NotFound) => {
+            return Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Thread not found"
+            })));
+        }
+        // Closing twice is a no-op: no new event and no second webhook
+        Err(CloseThreadError::AlreadyClosed(thread)) => {
+            return Ok(HttpResponse::Ok().json(thread));
+        }
+        Err(CloseThreadError::Database(e)) => {
+            eprintln!("Database error closing thread: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to close thread"
+            })));
+        }
+    };
+
+    // The bot passes notify=false for closes it already announced itself
+    if let Some(close_info) = close_data.filter(|c| c.notify.unwrap_or(true)) {
+        send_thread_closed_webhook(&thread, &close_info.closed_by_id, &close_info.closed_by_tag);
+    }
+
+    Ok(HttpResponse::Ok().json(updated_thread))
+}
+
+#[post("/threads/{id}/reopen")]
+async fn reopen_thread(
+    pool: web::Data<PgPool>,
+    thread_id: web::Path<i32>,
+    reopen_data: Option<web::Json<ReopenThread>>,
+) -> Result<impl Responder> {
+    let thread_id = thread_id.into_inner();
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Database error starting transaction: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to reopen thread"
+            })));
+        }
+    };
+
+    let thread_result =
+        sqlx::query_as::<_, db::Thread>("SELECT * FROM threads WHERE id = $1 FOR UPDATE")
+            .bind(thread_id)
+            .fetch_one(&mut *tx)
+            .await;
+
+    let thread = match thread_result {
+        Ok(thread) => thread,
+        Err(sqlx::Error::RowNotFound) => {
+            return Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Thread not found"
+            })));
+        }
+        Err(e) => {
+            eprintln!("Database error fetching thread: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch thread"
+            })));
+        }
+    };
+
+    if thread.is_open {
+        return Ok(HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Thread is already open"
+        })));
+    }
+
+    let updated_thread_result = sqlx::query_as::<_, db::Thread>(
+        r#"
+        UPDATE threads
+        SET is_open = TRUE, status = 'open', closed_at = NULL, closed_by_id = NULL, closed_by_tag = NULL, close_reason = NULL
+        WHERE id = $1
+        RETURNING *
+        "#,
+    )
+    .bind(thread_id)
+    .fetch_one(&mut *tx)
+    .await;
+
+    let updated_thread = match updated_thread_result {
+        Ok(thread) => thread,
+        // A user may only have one open thread at a time
+        Err(sqlx::Error::Database(db_err))
+            if db_err.constraint() == Some("idx_threads_user_open") =>
+        {
+            return Ok(HttpResponse::Conflict().json(serde_json::json!({
+                "error": "User already has an open thread. Please close existing thread first."
+            })));
+        }
+        Err(e) => {
+            eprintln!("Database error reopening thread: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to reopen thread"
+            })));
+        }
+    };
+
+    let event_result = thread_events::record_event(
+        &mut *tx,
+        thread_id,
+        thread_events::EVENT_REOPEN,
+        reopen_data.as_ref().map(|r| r.reopened_by_id.as_str()),
+        reopen_data.as_ref().map(|r| r.reopened_by_tag.as_str()),
+        reopen_data.as_ref().and_then(|r| r.reason.as_deref()),
+        None,
+    )
+    .await;
+
+    if let Err(e) = event_result {
+        eprintln!("Database error recording thread reopen event: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to reopen thread"
+        })));
+    }
+
+    if let Err(e) = tx.commit().await {
+        eprintln!("Database error committing thread reopen: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to reopen thread"
+        })));
+    }
+
+    Ok(HttpResponse::Ok().json(updated_thread))
+}
+
+#[derive(Deserialize)]
+struct AddMessageQuery {
+    allow_closed: Option<bool>,
+}
+
+#[post("/threads/{id}/messages")]
+async fn add_message_to_thread(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    path: web::Path<i32>,
+    query: web::Query<AddMessageQuery>,
+    message: web::Json<CreateMessage>,
+) -> Result<impl Responder> {
+    // Validate author ID format (Discord IDs are numeric)
+    if !message.author_id.chars().all(|c| c.is_ascii_digit()) {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Invalid author ID format"
+        })));
+    }
+
+    let requested_kind = match messages::requested_kind(&message) {
+        Ok(kind) => kind,
+        Err(response) => return Ok(response),
+    };
+
+    let attachments = message.attachments.clone().unwrap_or_default();
+    if let Err(response) = attachments::validate_attachments(&attachments) {
+        return Ok(response);
+    }
+
+    let thread_id = path.into_inner();
+    let thread_message_id = Uuid::new_v4();
+    let created_at = chrono::Utc::now();
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Database error starting transaction: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to create message"
+            })));
+        }
+    };
+
+    // Lock the thread so a concurrent close can't slip in between the check and the insert
+    let thread_result =
+        sqlx::query_as::<_, db::Thread>("SELECT * FROM threads WHERE id = $1 FOR UPDATE")
+            .bind(thread_id)
+            .fetch_one(&mut *tx)
+            .await;
+
+    let thread = match thread_result {
+        Ok(thread) => thread,
+        Err(sqlx::Error::RowNotFound) => {
+            return Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Thread not found"
+            })));
+        }
+        Err(e) => {
+            eprintln!("Database error fetching thread: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch thread"
+            })));
+        }
+    };
+
+    if !thread.is_open && !query.allow_closed.unwrap_or(false) {
+        return Ok(HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Thread is closed"
+        })));
+    }
+
+    let kind = match requested_kind {
+        Some(kind) => kind,
+        None if message.author_id == thread.user_id => messages::KIND_USER_INBOUND,
+        None => messages::KIND_STAFF_REPLY,
+    };
+
+    let new_message_result = sqlx::query_as::<_, db::Message>(
+        "INSERT INTO messages (id, author_id, author_tag, content, created_at, discord_message_id, kind) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *",
+    )
+    .bind(thread_message_id)
+    .bind(&message.author_id)
+    .bind(&message.author_tag)
+    .bind(&message.content)
+    .bind(created_at)
+    .bind(&message.discord_message_id)
+    .bind(kind)
+    .fetch_one(&mut *tx)
+    .await;
+
+    let mut new_message = match new_message_result {
+        Ok(message) => message,
+        Err(sqlx::Error::Database(db_err)) => {
+            if let Some(constraint) = db_err.constraint() {
+                match constraint {
+                    "chk_author_id_format" => {
+                        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                            "error": "Invalid author ID format"
+                        })));
+                    }
+                    "idx_messages_discord_message_id" => {
+                        return Ok(HttpResponse::Conflict().json(serde_json::json!({
+                            "error": "A message with this Discord message ID already exists"
+                        })));
+                    }
+                    _ => {
+                        eprintln!("Database constraint violation: {}", constraint);
+                        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                            "error": "Data validation failed"
+                        })));
+                    }
                 }
             } else {
                 eprintln!("Database error creating message: {}", db_err);
@@ -372,9 +1190,9 @@ async fn add_message_to_thread(
 
     let link_result =
         sqlx::query("INSERT INTO thread_messages (thread_id, message_id) VALUES ($1, $2)")
-            .bind(path.into_inner())
-            .bind(&thread_message_id)
-            .execute(pool.get_ref())
+            .bind(thread_id)
+            .bind(thread_message_id)
+            .execute(&mut *tx)
             .await;
 
     if let Err(e) = link_result {
@@ -384,6 +1202,57 @@ async fn add_message_to_thread(
         })));
     }
 
+    match attachments::insert_attachments(&mut tx, thread_message_id, &attachments).await {
+        Ok(inserted) => new_message.attachments = inserted,
+        Err(e) => {
+            eprintln!("Database error storing attachments: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to create message"
+            })));
+        }
+    }
+
+    // Bump last activity; a reply from the thread's user also puts the ball back in staff's court
+    let activity_result = sqlx::query(
+        r#"
+        UPDATE threads
+        SET updated_at = NOW(),
+            status = CASE WHEN user_id = $2 AND status NOT IN ($3, $4) THEN $3 ELSE status END
+        WHERE id = $1
+        "#,
+    )
+    .bind(thread_id)
+    .bind(&message.author_id)
+    .bind(STATUS_WAITING_ON_STAFF)
+    .bind(STATUS_CLOSED)
+    .execute(&mut *tx)
+    .await;
+
+    if let Err(e) = activity_result {
+        eprintln!("Database error updating thread activity: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to create message"
+        })));
+    }
+
+    // A reply from the user also calls off any pending "close unless they reply"
+    if let Err(e) =
+        scheduled_close::cancel_on_user_activity(&mut tx, thread_id, &message.author_id).await
+    {
+        eprintln!("Database error cancelling scheduled close: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to create message"
+        })));
+    }
+
+    if let Err(e) = tx.commit().await {
+        eprintln!("Database error committing message: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to create message"
+        })));
+    }
+
+    messages::hide_anonymous_authors(std::slice::from_mut(&mut new_message), auth::is_admin(&req));
     Ok(HttpResponse::Ok().json(new_message))
 }
 
@@ -396,51 +1265,303 @@ async fn update_thread_urgency(
     let thread_id = thread_id.into_inner();
 
     // Validate urgency level
-    let valid_urgencies = ["Low", "Medium", "High", "Urgent"];
-    if !valid_urgencies.contains(&urgency_data.urgency.as_str()) {
+    if !VALID_URGENCIES.contains(&urgency_data.urgency.as_str()) {
         return Ok(HttpResponse::BadRequest().json(serde_json::json!({
             "error": "Invalid urgency level. Must be one of: Low, Medium, High, Urgent"
         })));
     }
 
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Database error starting transaction: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to update thread urgency"
+            })));
+        }
+    };
+
+    let thread_result =
+        sqlx::query_as::<_, db::Thread>("SELECT * FROM threads WHERE id = $1 FOR UPDATE")
+            .bind(thread_id)
+            .fetch_one(&mut *tx)
+            .await;
+
+    let thread = match thread_result {
+        Ok(thread) => thread,
+        Err(sqlx::Error::RowNotFound) => {
+            return Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Thread not found"
+            })));
+        }
+        Err(e) => {
+            eprintln!("Database error fetching thread: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch thread"
+            })));
+        }
+    };
+
+    if thread.urgency == urgency_data.urgency {
+        return Ok(HttpResponse::Ok().json(thread));
+    }
+
     let updated_thread_result = sqlx::query_as::<_, db::Thread>(
         "UPDATE threads SET urgency = $1, updated_at = NOW() WHERE id = $2 RETURNING *",
     )
     .bind(&urgency_data.urgency)
     .bind(thread_id)
-    .fetch_one(pool.get_ref())
+    .fetch_one(&mut *tx)
     .await;
 
-    match updated_thread_result {
-        Ok(updated_thread) => Ok(HttpResponse::Ok().json(updated_thread)),
-        Err(sqlx::Error::RowNotFound) => Ok(HttpResponse::NotFound().json(serde_json::json!({
-            "error": "Thread not found"
-        }))),
-        Err(sqlx::Error::Database(db_err)) => {
-            if let Some(constraint) = db_err.constraint() {
-                match constraint {
-                    "chk_urgency" => Ok(HttpResponse::BadRequest().json(serde_json::json!({
-                        "error": "Invalid urgency level. Must be one of: Low, Medium, High, Urgent"
-                    }))),
-                    _ => {
-                        eprintln!("Database constraint violation: {}", constraint);
-                        Ok(HttpResponse::BadRequest().json(serde_json::json!({
-                            "error": "Data validation failed"
-                        })))
-                    }
-                }
-            } else {
-                eprintln!("Database error updating thread urgency: {}", db_err);
-                Ok(HttpResponse::InternalServerError().json(serde_json::json!({
-                    "error": "Failed to update thread urgency"
-                })))
-            }
+    let updated_thread = match updated_thread_result {
+        Ok(updated_thread) => updated_thread,
+        Err(sqlx::Error::Database(db_err)) if db_err.constraint() == Some("chk_urgency") => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid urgency level. Must be one of: Low, Medium, High, Urgent"
+            })));
         }
         Err(e) => {
             eprintln!("Database error updating thread urgency: {}", e);
-            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
                 "error": "Failed to update thread urgency"
-            })))
+            })));
+        }
+    };
+
+    let event_result = thread_events::record_event(
+        &mut *tx,
+        thread_id,
+        thread_events::EVENT_URGENCY_CHANGE,
+        urgency_data.changed_by_id.as_deref(),
+        urgency_data.changed_by_tag.as_deref(),
+        None,
+        Some(serde_json::json!({ "from": thread.urgency, "to": urgency_data.urgency })),
+    )
+    .await;
+
+    if let Err(e) = event_result {
+        eprintln!("Database error recording urgency change event: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to update thread urgency"
+        })));
+    }
+
+    if let Err(e) = tx.commit().await {
+        eprintln!("Database error committing thread urgency: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to update thread urgency"
+        })));
+    }
+
+    Ok(HttpResponse::Ok().json(updated_thread))
+}
+
+#[put("/threads/{id}/status")]
+async fn update_thread_status(
+    pool: web::Data<PgPool>,
+    thread_id: web::Path<i32>,
+    status_data: web::Json<UpdateThreadStatus>,
+) -> Result<impl Responder> {
+    let thread_id = thread_id.into_inner();
+
+    // Validate status
+    if !VALID_STATUSES.contains(&status_data.status.as_str()) {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Invalid status. Must be one of: open, waiting_on_user, waiting_on_staff, on_hold, resolved, closed"
+        })));
+    }
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Database error starting transaction: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to update thread status"
+            })));
+        }
+    };
+
+    let thread_result =
+        sqlx::query_as::<_, db::Thread>("SELECT * FROM threads WHERE id = $1 FOR UPDATE")
+            .bind(thread_id)
+            .fetch_one(&mut *tx)
+            .await;
+
+    let thread = match thread_result {
+        Ok(thread) => thread,
+        Err(sqlx::Error::RowNotFound) => {
+            return Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Thread not found"
+            })));
+        }
+        Err(e) => {
+            eprintln!("Database error fetching thread: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch thread"
+            })));
+        }
+    };
+
+    if thread.status == status_data.status {
+        return Ok(HttpResponse::Ok().json(thread));
+    }
+
+    let allowed = allowed_status_transitions(&thread.status);
+    if !allowed.contains(&status_data.status.as_str()) {
+        return Ok(HttpResponse::Conflict().json(serde_json::json!({
+            "error": format!(
+                "Cannot change status from {} to {}. Use the close and reopen endpoints to close or reopen threads.",
+                thread.status, status_data.status
+            ),
+            "allowed_statuses": allowed
+        })));
+    }
+
+    let updated_thread_result =
+        sqlx::query_as::<_, db::Thread>("UPDATE threads SET status = $1 WHERE id = $2 RETURNING *")
+            .bind(&status_data.status)
+            .bind(thread_id)
+            .fetch_one(&mut *tx)
+            .await;
+
+    let updated_thread = match updated_thread_result {
+        Ok(thread) => thread,
+        Err(e) => {
+            eprintln!("Database error updating thread status: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to update thread status"
+            })));
+        }
+    };
+
+    let event_result = thread_events::record_event(
+        &mut *tx,
+        thread_id,
+        thread_events::EVENT_STATUS_CHANGE,
+        status_data.changed_by_id.as_deref(),
+        status_data.changed_by_tag.as_deref(),
+        status_data.reason.as_deref(),
+        Some(serde_json::json!({ "from": thread.status, "to": status_data.status })),
+    )
+    .await;
+
+    if let Err(e) = event_result {
+        eprintln!("Database error recording status change event: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to update thread status"
+        })));
+    }
+
+    if let Err(e) = tx.commit().await {
+        eprintln!("Database error committing thread status: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to update thread status"
+        })));
+    }
+
+    Ok(HttpResponse::Ok().json(updated_thread))
+}
+
+#[put("/threads/{id}/channel")]
+async fn update_thread_channel(
+    pool: web::Data<PgPool>,
+    thread_id: web::Path<i32>,
+    channel_data: web::Json<UpdateThreadChannel>,
+) -> Result<impl Responder> {
+    let thread_id = thread_id.into_inner();
+
+    // Validate channel ID format (Discord IDs are numeric)
+    if channel_data.thread_id.is_empty()
+        || !channel_data.thread_id.chars().all(|c| c.is_ascii_digit())
+    {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Invalid thread ID format"
+        })));
+    }
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Database error starting transaction: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to update thread channel"
+            })));
+        }
+    };
+
+    let thread_result =
+        sqlx::query_as::<_, db::Thread>("SELECT * FROM threads WHERE id = $1 FOR UPDATE")
+            .bind(thread_id)
+            .fetch_one(&mut *tx)
+            .await;
+
+    let thread = match thread_result {
+        Ok(thread) => thread,
+        Err(sqlx::Error::RowNotFound) => {
+            return Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Thread not found"
+            })));
+        }
+        Err(e) => {
+            eprintln!("Database error fetching thread: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch thread"
+            })));
+        }
+    };
+
+    if thread.thread_id == channel_data.thread_id {
+        return Ok(HttpResponse::Ok().json(thread));
+    }
+
+    let updated_thread_result = sqlx::query_as::<_, db::Thread>(
+        "UPDATE threads SET thread_id = $1 WHERE id = $2 RETURNING *",
+    )
+    .bind(&channel_data.thread_id)
+    .bind(thread_id)
+    .fetch_one(&mut *tx)
+    .await;
+
+    let updated_thread = match updated_thread_result {
+        Ok(thread) => thread,
+        Err(sqlx::Error::Database(db_err)) if db_err.constraint() == Some("uk_user_thread") => {
+            return Ok(HttpResponse::Conflict().json(serde_json::json!({
+                "error": "A thread with this user and thread ID already exists"
+            })));
+        }
+        Err(e) => {
+            eprintln!("Database error updating thread channel: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to update thread channel"
+            })));
         }
+    };
+
+    let event_result = thread_events::record_event(
+        &mut *tx,
+        thread_id,
+        thread_events::EVENT_CHANNEL_CHANGE,
+        channel_data.changed_by_id.as_deref(),
+        channel_data.changed_by_tag.as_deref(),
+        None,
+        Some(serde_json::json!({ "from": thread.thread_id, "to": channel_data.thread_id })),
+    )
+    .await;
+
+    if let Err(e) = event_result {
+        eprintln!("Database error recording channel change event: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to update thread channel"
+        })));
     }
+
+    if let Err(e) = tx.commit().await {
+        eprintln!("Database error committing thread channel: {}", e);
+        return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": "Failed to update thread channel"
+        })));
+    }
+
+    Ok(HttpResponse::Ok().json(updated_thread))
 }
@@ -0,0 +1,626 @@
+use crate::attachments;
+use crate::auth;
+use crate::db;
+use crate::messages;
+use crate::storage::AttachmentStore;
+use crate::thread_events;
+use actix_web::{get, http::header, web, HttpRequest, HttpResponse, Responder};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+// Keeps a transcript of a screenshot-heavy thread from ballooning to hundreds of MB
+const MAX_EMBEDDED_IMAGE_BYTES: i64 = 8 * 1024 * 1024;
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S UTC";
+
+#[derive(Deserialize)]
+pub struct TranscriptQuery {
+    pub format: Option<String>,
+    pub include_notes: Option<bool>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum TranscriptEntry {
+    Message {
+        message: db::Message,
+    },
+    Note {
+        note: db::Note,
+    },
+    Event {
+        event: db::ThreadEvent,
+    },
+}
+
+impl TranscriptEntry {
+    fn created_at(&self) -> chrono::DateTime<chrono::Utc> {
+        match self {
+            TranscriptEntry::Message { message } => message.created_at,
+            TranscriptEntry::Note { note } => note.created_at,
+            TranscriptEntry::Event { event } => event.created_at,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct Transcript {
+    pub thread: db::Thread,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    generated_at: chrono::DateTime<chrono::Utc>,
+    entries: Vec<TranscriptEntry>,
+}
+
+impl Transcript {
+    // For transcripts handed to the user: staff show up under the anonymous name, and internal
+    // reasons and assignments are left out. The user's own actions keep their name.
+    pub fn hide_staff_details(&mut self) {
+        let display_name = messages::anonymous_display_name();
+        let thread = &mut self.thread;
+
+        if thread.closed_by_id.is_some() && thread.closed_by_id.as_ref() != Some(&thread.user_id) {
+            thread.closed_by_id = None;
+            thread.closed_by_tag = Some(display_name.clone());
+        }
+        thread.close_reason = None;
+        thread.assigned_to_id = None;
+        thread.assigned_to_tag = None;
+        thread.assigned_at = None;
+
+        for entry in &mut self.entries {
+            if let TranscriptEntry::Event { event } = entry {
+                if event.actor_id.is_some() && event.actor_id.as_ref() != Some(&thread.user_id) {
+                    event.actor_id = None;
+                    event.actor_tag = Some(display_name.clone());
+                }
+                event.reason = None;
+            }
+        }
+    }
+}
+
+pub async fn load_transcript(
+    pool: &PgPool,
+    thread_id: i32,
+    include_notes: bool,
+    include_deleted_notes: bool,
+    is_admin: bool,
+) -> Result<Option<Transcript>, sqlx::Error> {
+    let thread = match sqlx::query_as::<_, db::Thread>("SELECT * FROM threads WHERE id = $1")
+        .bind(thread_id)
+        .fetch_optional(pool)
+        .await?
+    {
+        Some(thread) => thread,
+        None => return Ok(None),
+    };
+
+    let mut thread_messages = sqlx::query_as::<_, db::Message>(
+        r#"
+        SELECT m.* FROM messages m
+        JOIN thread_messages tm ON m.id = tm.message_id
+        WHERE tm.thread_id = $1
+        ORDER BY m.created_at ASC, m.id ASC
+        "#,
+    )
+    .bind(thread_id)
+    .fetch_all(pool)
+    .await?;
+    attachments::attach_to_messages(pool, &mut thread_messages).await?;
+    messages::hide_anonymous_authors(&mut thread_messages, is_admin);
+
+    let events = sqlx::query_as::<_, db::ThreadEvent>(
+        "SELECT * FROM thread_events WHERE thread_id = $1 ORDER BY created_at ASC, id ASC",
+    )
+    .bind(thread_id)
+    .fetch_all(pool)
+    .await?;
+
+    // Events go first so that "opened" sorts ahead of the message that opened the thread
+    let mut entries: Vec<TranscriptEntry> = events
+        .into_iter()
+        .map(|event| TranscriptEntry::Event { event })
+        .collect();
+
+    entries.extend(
+        thread_messages
+            .into_iter()
+            .map(|message| TranscriptEntry::Message { message }),
+    );
+
+    if include_notes {
+        let notes = sqlx::query_as::<_, db::Note>(
+            "SELECT * FROM notes WHERE thread_id = $1 AND ($2 OR deleted_at IS NULL) ORDER BY created_at ASC, id ASC",
+        )
+        .bind(thread_id)
+        .bind(include_deleted_notes)
+        .fetch_all(pool)
+        .await?;
+        entries.extend(notes.into_iter().map(|note| TranscriptEntry::Note { note }));
+    }
+
+    // Stable sort keeps each source's own ordering for rows with the same timestamp
+    entries.sort_by_key(|entry| entry.created_at());
+
+    Ok(Some(Transcript {
+        thread,
+        generated_at: chrono::Utc::now(),
+        entries,
+    }))
+}
+
+fn format_timestamp(timestamp: chrono::DateTime<chrono::Utc>) -> String {
+    timestamp.format(TIMESTAMP_FORMAT).to_string()
+}
+
+fn metadata_str<'a>(event: &'a db::ThreadEvent, key: &str) -> &'a str {
+    event
+        .metadata
+        .get(key)
+        .and_then(|value| value.as_str())
+        .unwrap_or("?")
+}
+
+fn describe_event(event: &db::ThreadEvent) -> String {
+    let actor = event.actor_tag.as_deref().unwrap_or("System");
+    let description = match event.event_type.as_str() {
+        thread_events::EVENT_OPEN => format!("Thread opened by {}", actor),
+        thread_events::EVENT_CLOSE => format!("Thread closed by {}", actor),
+        thread_events::EVENT_REOPEN => format!("Thread reopened by {}", actor),
+        thread_events::EVENT_STATUS_CHANGE => format!(
+            "Status changed from {} to {} by {}",
+            metadata_str(event, "from"),
+            metadata_str(event, "to"),
+            actor
+        ),
+        thread_events::EVENT_URGENCY_CHANGE => format!(
+            "Urgency changed from {} to {} by {}",
+            metadata_str(event, "from"),
+            metadata_str(event, "to"),
+            actor
+        ),
+        thread_events::EVENT_CLOSE_SCHEDULED => format!("Close scheduled by {}", actor),
+        thread_events::EVENT_CLOSE_CANCELLED => format!("Scheduled close cancelled by {}", actor),
+        thread_events::EVENT_MERGE => match (
+            event.metadata.get("source_thread_id"),
+            event.metadata.get("target_thread_id"),
+        ) {
+            (Some(source), _) => format!("Thread #{} merged into this one by {}", source, actor),
+            (_, Some(target)) => format!("Merged into thread #{} by {}", target, actor),
+            _ => format!("Threads merged by {}", actor),
+        },
+        thread_events::EVENT_CHANNEL_CHANGE => format!(
+            "Channel changed from {} to {} by {}",
+            metadata_str(event, "from"),
+            metadata_str(event, "to"),
+            actor
+        ),
+        other => format!("{} by {}", other, actor),
+    };
+
+    match &event.reason {
+        Some(reason) if !reason.is_empty() => format!("{} (reason: {})", description, reason),
+        _ => description,
+    }
+}
+
+fn message_markers(message: &db::Message) -> Vec<String> {
+    let mut markers = Vec::new();
+    if let Some(edited_at) = message.edited_at {
+        markers.push(format!("edited {}", format_timestamp(edited_at)));
+    }
+    if let Some(deleted_at) = message.deleted_at {
+        markers.push(format!("deleted {}", format_timestamp(deleted_at)));
+    }
+    markers
+}
+
+fn note_markers(note: &db::Note) -> Vec<String> {
+    let mut markers = Vec::new();
+    if let Some(edited_at) = note.edited_at {
+        markers.push(format!("edited {}", format_timestamp(edited_at)));
+    }
+    if let Some(deleted_at) = note.deleted_at {
+        markers.push(format!("deleted {}", format_timestamp(deleted_at)));
+    }
+    markers
+}
+
+fn close_summary(thread: &db::Thread) -> Option<String> {
+    if thread.is_open {
+        return None;
+    }
+    let closed_at = thread.closed_at?;
+    let mut summary = format!(
+        "Closed {} by {}",
+        format_timestamp(closed_at),
+        thread.closed_by_tag.as_deref().unwrap_or("System")
+    );
+    if let Some(reason) = thread.close_reason.as_deref().filter(|r| !r.is_empty()) {
+        summary.push_str(&format!(" (reason: {})", reason));
+    }
+    Some(summary)
+}
+
+fn thread_header_lines(transcript: &Transcript) -> Vec<String> {
+    let thread = &transcript.thread;
+    let mut lines = vec![
+        format!("User: {}", thread.user_id),
+        format!("Channel: {}", thread.thread_id),
+        format!("Status: {}", thread.status),
+        format!("Urgency: {}", thread.urgency),
+    ];
+    if let Some(created_at) = thread.created_at {
+        lines.push(format!("Opened: {}", format_timestamp(created_at)));
+    }
+    if let Some(summary) = close_summary(thread) {
+        lines.push(summary);
+    }
+    lines.push(format!(
+        "Generated: {}",
+        format_timestamp(transcript.generated_at)
+    ));
+    lines
+}
+
+pub fn render_text(transcript: &Transcript) -> String {
+    let mut out = format!("Modmail transcript - thread #{}\n", transcript.thread.id);
+    for line in thread_header_lines(transcript) {
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out.push('\n');
+
+    for entry in &transcript.entries {
+        match entry {
+            TranscriptEntry::Message { message } => {
+                out.push_str(&format!(
+                    "[{}] {}",
+                    format_timestamp(message.created_at),
+                    message.author_tag
+                ));
+                let markers = message_markers(message);
+                if !markers.is_empty() {
+                    out.push_str(&format!(" ({})", markers.join(", ")));
+                }
+                out.push_str(":\n");
+                for line in message.content.lines() {
+                    out.push_str(&format!("    {}\n", line));
+                }
+                for attachment in &message.attachments {
+                    out.push_str(&format!(
+                        "    [attachment] {} <{}>\n",
+                        attachment.filename, attachment.url
+                    ));
+                }
+            }
+            TranscriptEntry::Note { note } => {
+                out.push_str(&format!(
+                    "[{}] NOTE by {}",
+                    format_timestamp(note.created_at),
+                    note.author_tag
+                ));
+                let markers = note_markers(note);
+                if !markers.is_empty() {
+                    out.push_str(&format!(" ({})", markers.join(", ")));
+                }
+                out.push_str(":\n");
+                for line in note.content.lines() {
+                    out.push_str(&format!("    {}\n", line));
+                }
+            }
+            TranscriptEntry::Event { event } => {
+                out.push_str(&format!(
+                    "[{}] -- {} --\n",
+                    format_timestamp(event.created_at),
+                    describe_event(event)
+                ));
+            }
+        }
+    }
+
+    out
+}
+
+fn render_markdown(transcript: &Transcript) -> String {
+    let mut out = format!(
+        "# Modmail transcript - thread #{}\n\n",
+        transcript.thread.id
+    );
+    for line in thread_header_lines(transcript) {
+        out.push_str(&format!("- {}\n", line));
+    }
+    out.push('\n');
+
+    for entry in &transcript.entries {
+        match entry {
+            TranscriptEntry::Message { message } => {
+                out.push_str(&format!(
+                    "**{}** · {}",
+                    message.author_tag,
+                    format_timestamp(message.created_at)
+                ));
+                let markers = message_markers(message);
+                if !markers.is_empty() {
+                    out.push_str(&format!(" _({})_", markers.join(", ")));
+                }
+                out.push_str("\n\n");
+                for line in message.content.lines() {
+                    out.push_str(&format!("> {}\n", line));
+                }
+                for attachment in &message.attachments {
+                    let filename = attachment.filename.replace(['[', ']'], "_");
+                    if attachments::is_web_url(&attachment.url) {
+                        out.push_str(&format!("> 📎 [{}]({})\n", filename, attachment.url));
+                    } else {
+                        out.push_str(&format!("> 📎 {}\n", filename));
+                    }
+                }
+                out.push('\n');
+            }
+            TranscriptEntry::Note { note } => {
+                out.push_str(&format!(
+                    "📝 **Note by {}** · {}",
+                    note.author_tag,
+                    format_timestamp(note.created_at)
+                ));
+                let markers = note_markers(note);
+                if !markers.is_empty() {
+                    out.push_str(&format!(" _({})_", markers.join(", ")));
+                }
+                out.push_str("\n\n");
+                for line in note.content.lines() {
+                    out.push_str(&format!("> {}\n", line));
+                }
+                out.push('\n');
+            }
+            TranscriptEntry::Event { event } => {
+                out.push_str(&format!(
+                    "_{} · {}_\n\n",
+                    describe_event(event),
+                    format_timestamp(event.created_at)
+                ));
+            }
+        }
+    }
+
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+const HTML_STYLE: &str = r#"
+body { font-family: -apple-system, "Segoe UI", Roboto, sans-serif; background: #313338; color: #dbdee1; margin: 0; padding: 24px; }
+header { border-bottom: 1px solid #4e5058; margin-bottom: 16px; padding-bottom: 12px; }
+header h1 { font-size: 20px; margin: 0 0 8px; }
+header ul { list-style: none; margin: 0; padding: 0; color: #b5bac1; font-size: 14px; }
+.entry { margin: 12px 0; }
+.meta { font-size: 13px; color: #949ba4; }
+.author { font-weight: 600; color: #f2f3f5; margin-right: 8px; }
+.content { white-space: pre-wrap; word-wrap: break-word; margin-top: 4px; }
+.deleted .content { text-decoration: line-through; opacity: 0.6; }
+.note { background: #3f3b2a; border-left: 4px solid #f0b232; padding: 8px 12px; border-radius: 4px; }
+.event { text-align: center; font-size: 13px; color: #949ba4; font-style: italic; }
+.attachments img { display: block; max-width: 480px; max-height: 360px; margin-top: 6px; border-radius: 4px; }
+.attachments a { color: #00a8fc; display: block; margin-top: 4px; }
+"#;
+
+// Archived images are inlined as data URIs so the file still renders once Discord links expire
+async fn render_attachment_html(
+    attachment: &db::Attachment,
+    store: Option<&dyn AttachmentStore>,
+) -> String {
+    let filename = escape_html(&attachment.filename);
+    let is_image = attachment
+        .archived_content_type
+        .as_deref()
+        .is_some_and(|content_type| {
+            content_type.starts_with("image/") && attachments::is_inline_type(content_type)
+        });
+
+    if let (true, Some(store), Some(storage_key)) = (is_image, store, &attachment.storage_key) {
+        if attachment.size <= MAX_EMBEDDED_IMAGE_BYTES {
+            match store.get(storage_key).await {
+                Ok(Some(bytes)) => {
+                    return format!(
+                        "<img src=\"data:{};base64,{}\" alt=\"{}\" title=\"{}\">",
+                        escape_html(
+                            attachment
+                                .archived_content_type
+                                .as_deref()
+                                .unwrap_or_default()
+                        ),
+                        STANDARD.encode(bytes),
+                        filename,
+                        filename
+                    );
+                }
+                Ok(None) => {
+                    eprintln!(
+                        "Archived attachment {} is missing from storage",
+                        attachment.id
+                    );
+                }
+                Err(e) => {
+                    eprintln!("Storage error reading attachment {}: {}", attachment.id, e);
+                }
+            }
+        }
+    }
+
+    if !attachments::is_web_url(&attachment.url) {
+        return format!("<span>📎 {}</span>", filename);
+    }
+
+    format!(
+        "<a href=\"{}\" rel=\"noopener noreferrer\">📎 {}</a>",
+        escape_html(&attachment.url),
+        filename
+    )
+}
+
+pub async fn render_html(transcript: &Transcript, store: Option<&dyn AttachmentStore>) -> String {
+    let title = format!("Modmail transcript - thread #{}", transcript.thread.id);
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<header>\n<h1>{}</h1>\n<ul>\n",
+        title, HTML_STYLE, title
+    );
+    for line in thread_header_lines(transcript) {
+        out.push_str(&format!("<li>{}</li>\n", escape_html(&line)));
+    }
+    out.push_str("</ul>\n</header>\n<main>\n");
+
+    for entry in &transcript.entries {
+        match entry {
+            TranscriptEntry::Message { message } => {
+                let class = if message.deleted_at.is_some() {
+                    "entry message deleted"
+                } else {
+                    "entry message"
+                };
+                out.push_str(&format!(
+                    "<div class=\"{}\">\n<div class=\"meta\"><span class=\"author\">{}</span>{}",
+                    class,
+                    escape_html(&message.author_tag),
+                    format_timestamp(message.created_at)
+                ));
+                let markers = message_markers(message);
+                if !markers.is_empty() {
+                    out.push_str(&format!(" ({})", markers.join(", ")));
+                }
+                out.push_str(&format!(
+                    "</div>\n<div class=\"content\">{}</div>\n",
+                    escape_html(&message.content)
+                ));
+                if !message.attachments.is_empty() {
+                    out.push_str("<div class=\"attachments\">\n");
+                    for attachment in &message.attachments {
+                        out.push_str(&render_attachment_html(attachment, store).await);
+                        out.push('\n');
+                    }
+                    out.push_str("</div>\n");
+                }
+                out.push_str("</div>\n");
+            }
+            TranscriptEntry::Note { note } => {
+                let class = if note.deleted_at.is_some() {
+                    "entry note deleted"
+                } else {
+                    "entry note"
+                };
+                out.push_str(&format!(
+                    "<div class=\"{}\">\n<div class=\"meta\"><span class=\"author\">Note by {}</span>{}",
+                    class,
+                    escape_html(&note.author_tag),
+                    format_timestamp(note.created_at)
+                ));
+                let markers = note_markers(note);
+                if !markers.is_empty() {
+                    out.push_str(&format!(" ({})", markers.join(", ")));
+                }
+                out.push_str(&format!(
+                    "</div>\n<div class=\"content\">{}</div>\n</div>\n",
+                    escape_html(&note.content)
+                ));
+            }
+            TranscriptEntry::Event { event } => {
+                out.push_str(&format!(
+                    "<div class=\"entry event\">{} · {}</div>\n",
+                    escape_html(&describe_event(event)),
+                    format_timestamp(event.created_at)
+                ));
+            }
+        }
+    }
+
+    out.push_str("</main>\n</body>\n</html>\n");
+    out
+}
+
+#[get("/threads/{id}/transcript")]
+async fn get_thread_transcript(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    store: Option<web::Data<dyn AttachmentStore>>,
+    thread_id: web::Path<i32>,
+    query: web::Query<TranscriptQuery>,
+) -> impl Responder {
+    let thread_id = thread_id.into_inner();
+    let format = query.format.as_deref().unwrap_or("json");
+
+    let (content_type, extension) = match format {
+        "json" => ("application/json", "json"),
+        "html" => ("text/html; charset=utf-8", "html"),
+        "md" => ("text/markdown; charset=utf-8", "md"),
+        "txt" => ("text/plain; charset=utf-8", "txt"),
+        _ => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid format. Must be one of: html, md, txt, json"
+            }));
+        }
+    };
+
+    let transcript = match load_transcript(
+        pool.get_ref(),
+        thread_id,
+        query.include_notes.unwrap_or(false),
+        false,
+        auth::is_admin(&req),
+    )
+    .await
+    {
+        Ok(Some(transcript)) => transcript,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Thread not found"
+            }));
+        }
+        Err(e) => {
+            eprintln!("Database error building transcript: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to build transcript"
+            }));
+        }
+    };
+
+    let body = match format {
+        "html" => render_html(&transcript, store.as_ref().map(|store| store.get_ref())).await,
+        "md" => render_markdown(&transcript),
+        "txt" => render_text(&transcript),
+        _ => match serde_json::to_string(&transcript) {
+            Ok(body) => body,
+            Err(e) => {
+                eprintln!("Error serializing transcript: {}", e);
+                return HttpResponse::InternalServerError().json(serde_json::json!({
+                    "error": "Failed to build transcript"
+                }));
+            }
+        },
+    };
+
+    HttpResponse::Ok()
+        .content_type(content_type)
+        .insert_header((header::X_CONTENT_TYPE_OPTIONS, "nosniff"))
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            format!(
+                "inline; filename=\"transcript-{}.{}\"",
+                thread_id, extension
+            ),
+        ))
+        .body(body)
+}
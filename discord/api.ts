@@ -1,4 +1,4 @@
-import type { Thread, MessageData, Macro, Attachment } from './types.js';
+import type { Thread, MessageData, MessageKind, Macro, Attachment } from './types.js';
 
 const BACKEND_URL = process.env.PUBLIC_BACKEND_URL || 'http://localhost:8080';
 
@@ -20,20 +20,37 @@ export async function createThread(
 }
 
 export async function getThreadByUserId(userId: string): Promise<Thread | null> {
-	const response = await fetch(`${BACKEND_URL}/threads`);
-	const { threads } = (await response.json()) as { threads: Thread[] };
-	return threads.find((t) => t.user_id === userId && t.is_open) || null;
+	const response = await fetch(
+		`${BACKEND_URL}/threads/by-user/${encodeURIComponent(userId)}?open=true`
+	);
+	if (response.status === 404) return null;
+	return response.json() as Promise<Thread>;
 }
 
 export async function getThreadByChannelId(channelId: string): Promise<Thread | null> {
-	const response = await fetch(`${BACKEND_URL}/threads`);
-	const { threads } = (await response.json()) as { threads: Thread[] };
-	return threads.find((t) => t.thread_id === channelId) || null;
+	const response = await fetch(
+		`${BACKEND_URL}/threads/by-channel/${encodeURIComponent(channelId)}`
+	);
+	if (response.status === 404) return null;
+	return response.json() as Promise<Thread>;
 }
 
-export async function closeThread(threadId: number): Promise<Thread> {
+export async function closeThread(
+	threadId: number,
+	closedById: string,
+	closedByTag: string,
+	reason?: string
+): Promise<Thread> {
 	const response = await fetch(`${BACKEND_URL}/threads/${threadId}/close`, {
 		method: 'POST',
+		headers: { 'Content-Type': 'application/json' },
+		body: JSON.stringify({
+			closed_by_id: closedById,
+			closed_by_tag: closedByTag,
+			reason: reason || null,
+			// The bot announces the closure itself, so skip the webhook round-trip
+			notify: false,
+		}),
 	});
 	return response.json() as Promise<Thread>;
 }
@@ -43,7 +60,12 @@ export async function addMessageToThread(
 	authorId: string,
 	authorTag: string,
 	content: string,
-	attachments: Attachment[] = []
+	options: {
+		attachments?: Attachment[];
+		discordMessageId?: string;
+		kind?: MessageKind;
+		anonymous?: boolean;
+	} = {}
 ): Promise<MessageData> {
 	const response = await fetch(`${BACKEND_URL}/threads/${threadId}/messages`, {
 		method: 'POST',
@@ -51,10 +73,40 @@ export async function addMessageToThread(
 		body: JSON.stringify({
 			author_id: authorId,
 			author_tag: authorTag,
+			content: content,
+			attachments: options.attachments ?? [],
+			discord_message_id: options.discordMessageId,
+			kind: options.kind,
+			anonymous: options.anonymous,
+		}),
+	});
+	return response.json() as Promise<MessageData>;
+}
+
+export async function editMessage(
+	discordMessageId: string,
+	editedById: string,
+	content: string,
+	attachments: Attachment[]
+): Promise<MessageData | null> {
+	const response = await fetch(`${BACKEND_URL}/messages/${discordMessageId}`, {
+		method: 'PATCH',
+		headers: { 'Content-Type': 'application/json' },
+		body: JSON.stringify({
 			content: content,
 			attachments: attachments,
+			edited_by_id: editedById,
 		}),
 	});
+	if (response.status === 404) return null;
+	return response.json() as Promise<MessageData>;
+}
+
+export async function deleteMessage(discordMessageId: string): Promise<MessageData | null> {
+	const response = await fetch(`${BACKEND_URL}/messages/${discordMessageId}`, {
+		method: 'DELETE',
+	});
+	if (response.status === 404) return null;
 	return response.json() as Promise<MessageData>;
 }
 
@@ -146,11 +198,54 @@ export async function unblockUser(userId: string): Promise<any> {
 	return response.json();
 }
 
-export async function updateThreadUrgency(threadId: number, urgency: string): Promise<Thread> {
+export async function updateThreadUrgency(
+	threadId: number,
+	urgency: string,
+	changedById?: string,
+	changedByTag?: string
+): Promise<Thread> {
 	const response = await fetch(`${BACKEND_URL}/threads/${threadId}/urgency`, {
 		method: 'PUT',
 		headers: { 'Content-Type': 'application/json' },
-		body: JSON.stringify({ urgency }),
+		body: JSON.stringify({
+			urgency,
+			changed_by_id: changedById,
+			changed_by_tag: changedByTag,
+		}),
 	});
 	return response.json() as Promise<Thread>;
 }
+
+export async function assignThread(
+	threadId: number,
+	assigneeId: string,
+	assigneeTag: string,
+	force = false
+): Promise<Response> {
+	return fetch(`${BACKEND_URL}/threads/${threadId}/assign`, {
+		method: 'POST',
+		headers: { 'Content-Type': 'application/json' },
+		body: JSON.stringify({
+			assignee_id: assigneeId,
+			assignee_tag: assigneeTag,
+			assigned_by_id: assigneeId,
+			assigned_by_tag: assigneeTag,
+			force,
+		}),
+	});
+}
+
+export async function unassignThread(
+	threadId: number,
+	unassignedById: string,
+	unassignedByTag: string
+): Promise<Response> {
+	return fetch(`${BACKEND_URL}/threads/${threadId}/unassign`, {
+		method: 'POST',
+		headers: { 'Content-Type': 'application/json' },
+		body: JSON.stringify({
+			unassigned_by_id: unassignedById,
+			unassigned_by_tag: unassignedByTag,
+		}),
+	});
+}
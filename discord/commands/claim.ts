@@ -0,0 +1,105 @@
+import { ChatInputCommandInteraction, MessageFlagsBitField } from 'discord.js';
+import { assignThread, getThreadByChannelId, unassignThread } from '../api.js';
+
+export async function handleClaimCommand(interaction: ChatInputCommandInteraction) {
+	const force = interaction.options.getBoolean('force') ?? false;
+
+	try {
+		// Get thread from current channel
+		const thread = await getThreadByChannelId(interaction.channelId);
+
+		if (!thread) {
+			await interaction.reply({
+				content: '❌ This command can only be used in modmail threads.',
+				flags: MessageFlagsBitField.Flags.Ephemeral,
+			});
+			return;
+		}
+
+		const response = await assignThread(
+			thread.id,
+			interaction.user.id,
+			interaction.user.tag,
+			force
+		);
+
+		if (response.status === 409) {
+			const { error, assigned_to_tag } = (await response.json()) as {
+				error: string;
+				assigned_to_tag?: string;
+			};
+			await interaction.reply({
+				content: assigned_to_tag
+					? `❌ This thread is already claimed by ${assigned_to_tag}. Use \`force\` to take it over.`
+					: `❌ ${error}.`,
+				flags: MessageFlagsBitField.Flags.Ephemeral,
+			});
+			return;
+		}
+
+		if (!response.ok) {
+			throw new Error(`Backend responded with ${response.status}`);
+		}
+
+		await interaction.reply({
+			content: '✅ You have claimed this thread.',
+			flags: MessageFlagsBitField.Flags.Ephemeral,
+		});
+
+		// Send notification to channel
+		if (interaction.channel?.isTextBased() && 'send' in interaction.channel) {
+			await interaction.channel.send(`🙋 **Thread claimed** by ${interaction.user.tag}`);
+		}
+	} catch (error) {
+		console.error('Error claiming thread:', error);
+		await interaction.reply({
+			content: '❌ Failed to claim thread. Please try again.',
+			flags: MessageFlagsBitField.Flags.Ephemeral,
+		});
+	}
+}
+
+export async function handleUnclaimCommand(interaction: ChatInputCommandInteraction) {
+	try {
+		// Get thread from current channel
+		const thread = await getThreadByChannelId(interaction.channelId);
+
+		if (!thread) {
+			await interaction.reply({
+				content: '❌ This command can only be used in modmail threads.',
+				flags: MessageFlagsBitField.Flags.Ephemeral,
+			});
+			return;
+		}
+
+		const response = await unassignThread(thread.id, interaction.user.id, interaction.user.tag);
+
+		if (response.status === 409) {
+			await interaction.reply({
+				content: '❌ This thread is not claimed.',
+				flags: MessageFlagsBitField.Flags.Ephemeral,
+			});
+			return;
+		}
+
+		if (!response.ok) {
+			throw new Error(`Backend responded with ${response.status}`);
+		}
+
+		await interaction.reply({
+			content: '✅ Thread released.',
+			flags: MessageFlagsBitField.Flags.Ephemeral,
+		});
+
+		// Send notification to channel
+		if (interaction.channel?.isTextBased() && 'send' in interaction.channel) {
+			await interaction.channel.send(`👋 **Thread released** by ${interaction.user.tag}`);
+		}
+	} catch (error) {
+		console.error('Error releasing thread:', error);
+		await interaction.reply({
+			content: '❌ Failed to release thread. Please try again.',
+			flags: MessageFlagsBitField.Flags.Ephemeral,
+		});
+	}
+}
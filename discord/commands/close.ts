@@ -30,7 +30,7 @@ export async function handleCloseCommand(interaction: ChatInputCommandInteractio
 	}
 
 	// Close thread in database
-	await closeThread(thread.id);
+	await closeThread(thread.id, interaction.user.id, interaction.user.tag);
 
 	// Send closure message
 	const embed = createThreadClosedEmbed(interaction.user);
@@ -6,6 +6,7 @@ import { handleBlockCommand, handleUnblockCommand } from './block.js';
 import { handleMacroCommand } from './macro.js';
 import { handleDeleteCommand } from './delete.js';
 import { handleUrgencyCommand } from './urgency.js';
+import { handleClaimCommand, handleUnclaimCommand } from './claim.js';
 
 export async function handleSlashCommand(interaction: ChatInputCommandInteraction, client: Client) {
 	const { commandName } = interaction;
@@ -36,6 +37,12 @@ export async function handleSlashCommand(interaction: ChatInputCommandInteractio
 			case 'urgency':
 				await handleUrgencyCommand(interaction);
 				break;
+			case 'claim':
+				await handleClaimCommand(interaction);
+				break;
+			case 'unclaim':
+				await handleUnclaimCommand(interaction);
+				break;
 			default:
 				await interaction.reply({
 					content: '❌ Unknown command.',
@@ -89,7 +89,8 @@ async function handleMacroSend(interaction: ChatInputCommandInteraction, client:
 			thread.id,
 			interaction.user.id,
 			interaction.user.tag,
-			`[MACRO: ${macroName}] ${macro.content}`
+			`[MACRO: ${macroName}] ${macro.content}`,
+			{ kind: 'macro' }
 		);
 
 		// Confirm in channel
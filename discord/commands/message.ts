@@ -15,6 +15,7 @@ export async function handleMessageCommand(
 ) {
 	const user = interaction.options.getUser('user', true);
 	const messageContent = interaction.options.getString('message', true);
+	const anonymous = interaction.options.getBoolean('anonymous') ?? false;
 
 	try {
 		// Send DM to user
@@ -48,7 +49,9 @@ export async function handleMessageCommand(
 		}
 
 		// Add message to thread
-		await addMessageToThread(thread.id, interaction.user.id, interaction.user.tag, messageContent);
+		await addMessageToThread(thread.id, interaction.user.id, interaction.user.tag, messageContent, {
+			anonymous,
+		});
 
 		// Send confirmation to channel
 		const confirmEmbed = createConfirmationEmbed(user, messageContent);
@@ -37,7 +37,12 @@ export async function handleUrgencyCommand(interaction: ChatInputCommandInteract
 		}
 
 		// Update thread urgency
-		await updateThreadUrgency(thread.id, normalizedUrgency);
+		await updateThreadUrgency(
+			thread.id,
+			normalizedUrgency,
+			interaction.user.id,
+			interaction.user.tag
+		);
 
 		await interaction.reply({
 			content: `✅ Thread urgency updated to **${normalizedUrgency}**`,
@@ -37,7 +37,8 @@ export async function handleButtonInteraction(interaction: ButtonInteraction, cl
 			thread.id,
 			interaction.user.id,
 			interaction.user.tag,
-			`[QUICK REPLY: ${macroName}] ${macro.content}`
+			`[QUICK REPLY: ${macroName}] ${macro.content}`,
+			{ kind: 'macro' }
 		);
 
 		// Confirm in channel
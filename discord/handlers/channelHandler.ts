@@ -40,7 +40,7 @@ export async function handleChannelMessage(message: Message, client: Client) {
 			message.author.id,
 			message.author.tag,
 			message.content,
-			attachments
+			{ attachments }
 		);
 
 		// React to confirm message was sent
@@ -1,5 +1,6 @@
 import {
 	Message,
+	PartialMessage,
 	Client,
 	ChannelType,
 	ActionRowBuilder,
@@ -10,6 +11,8 @@ import {
 	getThreadByUserId,
 	createThread,
 	addMessageToThread,
+	editMessage,
+	deleteMessage,
 	isUserBlocked,
 	getMacros,
 } from '../api.js';
@@ -131,7 +134,7 @@ async function handleExistingThread(message: Message, client: Client, thread: an
 		message.author.id,
 		message.author.tag,
 		message.content,
-		attachments
+		{ attachments, discordMessageId: message.id }
 	);
 
 	// Forward message to modmail channel
@@ -153,5 +156,30 @@ async function handleExistingThread(message: Message, client: Client, thread: an
 	await message.author.send({ embeds: [confirmEmbed] });
 }
 
+export async function handleDirectMessageUpdate(message: Message | PartialMessage) {
+	if (message.channel.type !== ChannelType.DM) return;
+
+	try {
+		if (message.partial) message = await message.fetch();
+		if (message.author.bot) return;
+
+		const attachments = processAttachments(Array.from(message.attachments.values()));
+		await editMessage(message.id, message.author.id, message.content, attachments);
+	} catch (error) {
+		console.error('Error syncing edited DM:', error);
+	}
+}
+
+export async function handleDirectMessageDelete(message: Message | PartialMessage) {
+	if (message.channel.type !== ChannelType.DM) return;
+
+	try {
+		// Messages we never stored (bot messages, pre-thread DMs) simply 404
+		await deleteMessage(message.id);
+	} catch (error) {
+		console.error('Error syncing deleted DM:', error);
+	}
+}
+
 // Export function for use in interaction handler
 export { createThreadForUser };
@@ -2,7 +2,11 @@ import { Client, GatewayIntentBits, Events, MessageFlagsBitField, Partials } fro
 import express, { type Request, type Response } from 'express';
 import 'dotenv/config';
 import { handleSlashCommand } from './commands/index.js';
-import { handleDirectMessage } from './handlers/dmHandler.js';
+import {
+	handleDirectMessage,
+	handleDirectMessageUpdate,
+	handleDirectMessageDelete,
+} from './handlers/dmHandler.js';
 import { handleChannelMessage } from './handlers/channelHandler.js';
 import { handleWebhookThreadClosed } from './webhookHandler.js';
 import { handleButtonInteraction } from './handlers/buttonHandler.js';
@@ -20,7 +24,7 @@ const client = new Client({
 		GatewayIntentBits.DirectMessages,
 		GatewayIntentBits.MessageContent,
 	],
-	partials: [Partials.Channel],
+	partials: [Partials.Channel, Partials.Message],
 });
 
 client.once(Events.ClientReady, (readyClient) => {
@@ -145,6 +149,15 @@ client.on(Events.MessageCreate, async (message) => {
 	await handleDirectMessage(message, client);
 });
 
+// Keep stored DMs in sync when users edit or delete them
+client.on(Events.MessageUpdate, async (_oldMessage, newMessage) => {
+	await handleDirectMessageUpdate(newMessage);
+});
+
+client.on(Events.MessageDelete, async (message) => {
+	await handleDirectMessageDelete(message);
+});
+
 // Handle messages in modmail channels (relay to user)
 client.on(Events.MessageCreate, async (message) => {
 	await handleChannelMessage(message, client);
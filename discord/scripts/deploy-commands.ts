@@ -12,6 +12,12 @@ const commands = [
 		)
 		.addStringOption((option) =>
 			option.setName('message').setDescription('The message to send').setRequired(true)
+		)
+		.addBooleanOption((option) =>
+			option
+				.setName('anonymous')
+				.setDescription('Hide your name from the stored transcript')
+				.setRequired(false)
 		),
 	new SlashCommandBuilder().setName('close').setDescription('Close the current thread'),
 	new SlashCommandBuilder().setName('delete').setDescription('Delete the current thread'),
@@ -51,6 +57,16 @@ const commands = [
 					{ name: 'Urgent', value: 'Urgent' }
 				)
 		),
+	new SlashCommandBuilder()
+		.setName('claim')
+		.setDescription('Claim the current thread')
+		.addBooleanOption((option) =>
+			option
+				.setName('force')
+				.setDescription('Take the thread over from another moderator')
+				.setRequired(false)
+		),
+	new SlashCommandBuilder().setName('unclaim').setDescription('Release the current thread'),
 	new SlashCommandBuilder()
 		.setName('macro')
 		.setDescription('Manage macros')
@@ -3,8 +3,21 @@ export interface Thread {
 	user_id: string;
 	thread_id: string;
 	is_open: boolean;
+	closed_at?: number | null;
+	closed_by_id?: string | null;
+	closed_by_tag?: string | null;
+	close_reason?: string | null;
+	assigned_to_id?: string | null;
+	assigned_to_tag?: string | null;
 }
 
+export type MessageKind =
+	| 'user_inbound'
+	| 'staff_reply'
+	| 'staff_anonymous_reply'
+	| 'system'
+	| 'macro';
+
 export interface MessageData {
 	id: string;
 	author_id: string;
@@ -12,6 +25,10 @@ export interface MessageData {
 	content: string;
 	attachments: any[];
 	created_at: string;
+	kind?: MessageKind;
+	discord_message_id?: string | null;
+	edited_at?: number | null;
+	deleted_at?: number | null;
 }
 
 export interface Macro {
@@ -25,6 +42,7 @@ export interface Attachment {
 	filename: string;
 	content_type: string;
 	size: number;
+	discord_id?: string;
 }
 
 export interface BlockUserResponse {
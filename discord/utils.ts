@@ -102,6 +102,7 @@ export function processAttachments(messageAttachments: any[]): Attachment[] {
 		filename: attachment.name,
 		content_type: attachment.contentType || 'unknown',
 		size: attachment.size,
+		discord_id: attachment.id,
 	}));
 }
 
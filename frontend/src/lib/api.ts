@@ -1,10 +1,13 @@
 import { PUBLIC_BACKEND_URL } from '$env/static/public';
 
 export interface Attachment {
+	id?: string;
 	url: string;
 	filename: string;
-	content_type: string;
+	content_type: string | null;
 	size: number;
+	discord_id?: string | null;
+	archived_at?: number | null;
 }
 
 export interface Message {
@@ -39,6 +42,9 @@ export interface Note {
 	author_tag: string;
 	content: string;
 	created_at: number;
+	pinned: boolean;
+	edited_at: number | null;
+	deleted_at: number | null;
 }
 
 export interface BlockedUser {